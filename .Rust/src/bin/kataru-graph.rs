@@ -0,0 +1,48 @@
+//! Exports the passage graph of a Kataru story.
+//!
+//! Usage: `kataru-graph [--json] <story path> [output path]`
+//!
+//! Writes GraphViz DOT, or JSON if `--json` is passed or the output path ends in `.json`.
+//! Without an output path, the graph is printed to stdout.
+
+use kataru::*;
+use kataru_ffi::build_story_graph_file;
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage: kataru-graph [--json] <story path> [output path]";
+
+fn run(args: &[String]) -> Result<()> {
+    let json = args.iter().any(|arg| arg == "--json");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--json").collect();
+    let (story_path, output_path) = match paths.as_slice() {
+        [story_path] => (story_path.as_str(), None),
+        [story_path, output_path] => (story_path.as_str(), Some(output_path.as_str())),
+        _ => return Err(error!("{}", USAGE)),
+    };
+
+    let story = Story::load(story_path)?;
+    // The format is picked by extension, so `--json` overrides the output path's.
+    let format = match output_path {
+        _ if json => "graph.json",
+        Some(path) => path,
+        None => "graph.dot",
+    };
+    let graph = build_story_graph_file(&story, format)?;
+    match output_path {
+        Some(path) => {
+            if let Err(err) = fs::write(path, graph) {
+                return Err(error!("Error writing story graph to '{}': {}", path, err));
+            }
+        }
+        None => print!("{}", graph),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
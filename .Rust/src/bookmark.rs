@@ -3,6 +3,8 @@ use crate::RUNNER;
 use kataru::*;
 use std::os::raw::c_char;
 
+static mut STATE_JSON: String = String::new();
+
 /// Loads a bookmark if it exists.
 /// If `default` is `true`, on failure to load it will create a new default bookmark.
 fn try_load_bookmark(path: &str) -> Result<()> {
@@ -96,7 +98,7 @@ pub extern "C" fn get_passage() -> FFIStr {
 fn try_get_state(key: &str) -> String {
     let runner = unsafe {
         let Some(runner) = RUNNER.as_mut() else {
-            return "\'error\": \"Runner was not initialized.\"".to_string();
+            return "{\"error\": \"Runner was not initialized.\"}".to_string();
         };
         runner
     };
@@ -112,7 +114,10 @@ fn try_get_state(key: &str) -> String {
 }
 #[no_mangle]
 pub extern "C" fn get_state(key: *const c_char, length: usize) -> FFIStr {
-    FFIStr::from(&try_get_state(FFIStr::to_str(key, length)))
+    unsafe {
+        STATE_JSON = try_get_state(FFIStr::to_str(key, length));
+        FFIStr::from(&STATE_JSON)
+    }
 }
 
 fn try_set_line(line: usize) -> Result<()> {
@@ -0,0 +1,226 @@
+pub use crate::ffi::FFIStr;
+use crate::ids::fnv1a;
+use crate::namespaces::{qualify, resolution_order, resolve, split_qualified};
+use crate::sources::{load_sources, SourceFile};
+use crate::{ffi::json_or_error, RUNNER};
+use kataru::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::{fs, os::raw::c_char, path::Path};
+
+static mut CHARACTER_JSON: String = String::new();
+static mut CHARACTERS_JSON: String = String::new();
+static mut CHARACTER_CONFIGS: CharacterConfigs = CharacterConfigs::new();
+
+/// Raw character configs by namespace and then name, since kataru only keeps `description`.
+pub type CharacterConfigs = BTreeMap<String, BTreeMap<String, serde_json::Value>>;
+
+/// The configs saved alongside a compiled story, e.g. `story.bin.characters.json`.
+#[derive(Serialize, Deserialize)]
+struct CompiledConfigs {
+    /// Hash of the compiled story the configs were saved with.
+    story: String,
+    characters: CharacterConfigs,
+}
+
+fn compiled_configs_path(story_path: &str) -> String {
+    format!("{}.characters.json", story_path)
+}
+
+fn compiled_story_hash(story_path: &str) -> Result<String> {
+    match fs::read(story_path) {
+        Ok(bytes) => Ok(format!("{:016x}", fnv1a(bytes))),
+        Err(err) => Err(error!("Error reading story '{}': {}", story_path, err)),
+    }
+}
+
+/// Gets the character configs declared in `sources`.
+pub fn source_character_configs(sources: &[SourceFile]) -> CharacterConfigs {
+    let mut configs = CharacterConfigs::new();
+    for source in sources {
+        let Some(serde_yaml::Value::Mapping(characters)) = source.config.get("characters") else {
+            continue;
+        };
+        let namespace_configs = configs.entry(source.namespace.clone()).or_default();
+        for (name, config) in characters {
+            let (Some(name), Ok(config)) = (name.as_str(), serde_json::to_value(config)) else {
+                continue;
+            };
+            namespace_configs.entry(name.to_string()).or_insert(config);
+        }
+    }
+    configs
+}
+
+/// Reads the character configs of the story at `story_path`.
+/// Compiled stories must have the configs `save_story` writes alongside them.
+pub fn load_character_configs(story_path: &str) -> Result<CharacterConfigs> {
+    let path = Path::new(story_path);
+    if path.is_dir() {
+        return Ok(source_character_configs(&load_sources(story_path)));
+    }
+    if path
+        .extension()
+        .is_some_and(|ext| ext == "yml" || ext == "yaml")
+    {
+        return Ok(CharacterConfigs::new());
+    }
+
+    let configs_path = compiled_configs_path(story_path);
+    let Ok(json) = fs::read_to_string(&configs_path) else {
+        return Err(error!(
+            "Character configs for compiled story '{}' were not found at '{}'. Save the story again to write them.",
+            story_path, configs_path
+        ));
+    };
+    let configs: CompiledConfigs = match serde_json::from_str(&json) {
+        Ok(configs) => configs,
+        Err(err) => {
+            return Err(error!(
+                "Invalid character configs '{}': {}",
+                configs_path, err
+            ))
+        }
+    };
+    if configs.story != compiled_story_hash(story_path)? {
+        return Err(error!(
+            "Character configs '{}' were saved with a different version of '{}'. Save the story again to update them.",
+            configs_path, story_path
+        ));
+    }
+    Ok(configs.characters)
+}
+
+pub fn init_character_configs(story_path: &str) -> Result<()> {
+    unsafe { CHARACTER_CONFIGS = load_character_configs(story_path)? }
+    Ok(())
+}
+
+/// Saves the loaded character configs alongside the story compiled to `story_path`.
+pub fn save_character_configs(story_path: &str) -> Result<()> {
+    let path = compiled_configs_path(story_path);
+    let configs = CompiledConfigs {
+        story: compiled_story_hash(story_path)?,
+        characters: unsafe { CHARACTER_CONFIGS.clone() },
+    };
+    let json = serde_json::to_string(&configs).map_err(|err| error!("{}", err))?;
+    if let Err(err) = fs::write(&path, json) {
+        return Err(error!(
+            "Error writing character configs to '{}': {}",
+            path, err
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct CharacterInfo<'a> {
+    name: String,
+    namespace: &'a str,
+    config: serde_json::Value,
+}
+
+/// Falls back to the fields kataru parses if there is no raw config.
+fn character_config(
+    configs: &CharacterConfigs,
+    namespace: &str,
+    name: &str,
+    config: &Option<CharacterData>,
+) -> serde_json::Value {
+    match configs.get(namespace).and_then(|configs| configs.get(name)) {
+        Some(config) => config.clone(),
+        None => serde_json::to_value(config).unwrap_or_default(),
+    }
+}
+
+/// Gets the JSON config of character `name` as seen from `namespace`.
+pub fn build_character_config(
+    story: &Story,
+    configs: &CharacterConfigs,
+    namespace: &str,
+    name: &str,
+) -> Result<String> {
+    let (resolved_namespace, config) = match resolve(story, namespace, name, |section, name| {
+        section.character(name)
+    }) {
+        Ok(resolved) => resolved,
+        Err(e) => return Err(error!("Invalid character: {}", e)),
+    };
+    let name = split_qualified(namespace, name).1;
+    let character = CharacterInfo {
+        name: qualify(resolved_namespace, name),
+        namespace: resolved_namespace,
+        config: character_config(configs, resolved_namespace, name, config),
+    };
+    serde_json::to_string(&character).map_err(|err| error!("{}", err))
+}
+
+/// Lists every character visible from `namespace` as JSON, innermost namespace first.
+pub fn build_character_list(
+    story: &Story,
+    configs: &CharacterConfigs,
+    namespace: &str,
+) -> Result<String> {
+    let mut seen = Vec::<&str>::new();
+    let mut characters = Vec::<CharacterInfo>::new();
+    for resolved_namespace in resolution_order(namespace) {
+        let Some(section) = story.sections.get(resolved_namespace) else {
+            return Err(error!("Namespace '{}' does not exist", resolved_namespace));
+        };
+        let mut names: Vec<&String> = section.config.characters.keys().collect();
+        names.sort();
+        for name in names {
+            if seen.contains(&name.as_str()) {
+                continue;
+            }
+            seen.push(name);
+            characters.push(CharacterInfo {
+                name: qualify(resolved_namespace, name),
+                namespace: resolved_namespace,
+                config: character_config(
+                    configs,
+                    resolved_namespace,
+                    name,
+                    &section.config.characters[name],
+                ),
+            });
+        }
+    }
+    serde_json::to_string(&characters).map_err(|err| error!("{}", err))
+}
+
+fn try_get_character_config(name: &str) -> Result<String> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_ref() {
+            build_character_config(runner.story(), &CHARACTER_CONFIGS, runner.namespace(), name)
+        } else {
+            Err(error!("Runner was not initialized."))
+        }
+    }
+}
+#[no_mangle]
+pub extern "C" fn get_character_config(name: *const c_char, length: usize) -> FFIStr {
+    let name = FFIStr::to_str(name, length);
+    unsafe {
+        CHARACTER_JSON = json_or_error(try_get_character_config(name));
+        FFIStr::from(&CHARACTER_JSON)
+    }
+}
+
+fn try_list_characters(namespace: &str) -> Result<String> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_ref() {
+            build_character_list(runner.story(), &CHARACTER_CONFIGS, namespace)
+        } else {
+            Err(error!("Runner was not initialized."))
+        }
+    }
+}
+#[no_mangle]
+pub extern "C" fn list_characters(namespace: *const c_char, length: usize) -> FFIStr {
+    let namespace = FFIStr::to_str(namespace, length);
+    unsafe {
+        CHARACTERS_JSON = json_or_error(try_list_characters(namespace));
+        FFIStr::from(&CHARACTERS_JSON)
+    }
+}
@@ -1,7 +1,166 @@
-pub use crate::{ffi::FFIStr, LINE};
+use crate::ffi::json_or_error;
+use crate::ids::{find_line_id, line_ids, LineId};
+use crate::lines::{current_raw_line, flatten, FlatLine};
+use crate::locale::choice_label;
+use crate::richtext::{RichSpan, RichText};
+pub use crate::{ffi::FFIStr, LINE, RUNNER};
 use kataru::*;
+use serde::Serialize;
 
 static mut CHOICES: Vec<FFIStr> = Vec::new();
+static mut CHOICE_DETAILS_JSON: String = String::new();
+static mut TIMEOUT_DEFAULT_JSON: String = String::new();
+
+/// A choice along with where it leads and whether it can currently be chosen.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChoiceDetails {
+    /// The source key to pass to `next` when choosing.
+    pub label: String,
+    /// Translated text with attribute tags stripped.
+    pub text: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// None if the choice continues with embedded lines.
+    pub target: Option<String>,
+    pub available: bool,
+    /// The condition that failed if the choice is unavailable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<RichSpan>,
+}
+
+/// Builds details for every choice of the runner's current line, including unavailable ones.
+pub fn build_choice_details(runner: &Runner, line_ids: &[LineId]) -> Result<Vec<ChoiceDetails>> {
+    let Some(RawLine::Choices(raw)) = current_raw_line(runner) else {
+        return Err(error!("Current line is not a choice."));
+    };
+
+    // Choices without a target use the target of the next available choice, so iterate backwards.
+    // Like the runner, unavailable choices don't pass their target on.
+    let mut details = Vec::new();
+    let mut passage: Option<String> = None;
+    let mut add_target = |label: &str, target: &ChoiceTarget, reason: Option<&str>| {
+        let target = match target {
+            ChoiceTarget::PassageName(passage_name) => {
+                if reason.is_none() {
+                    passage = Some(passage_name.clone());
+                }
+                Some(passage_name.clone())
+            }
+            ChoiceTarget::None => passage.clone(),
+            ChoiceTarget::Lines(_) => None,
+        };
+        let label_text = choice_label(line_ids, label);
+        let rich_text = RichText::parse::<&str>(label_text, &[]);
+        details.push(ChoiceDetails {
+            label: label.to_string(),
+            text: rich_text.text,
+            id: find_line_id(line_ids, Some(label))
+                .unwrap_or_default()
+                .to_string(),
+            target,
+            available: reason.is_none(),
+            reason: reason.map(str::to_string),
+            attributes: rich_text.spans,
+        });
+    };
+    for (key, choice) in raw.into_iter().rev() {
+        match choice {
+            RawChoice::Target(target) => add_target(key, target, None),
+            RawChoice::Conditional(conditional) => {
+                let available = Value::from_conditional(key, runner.bookmark())?;
+                let reason = if available { None } else { Some(key.as_str()) };
+                for (inner_key, target) in conditional.iter().rev() {
+                    add_target(inner_key, target, reason);
+                }
+            }
+        }
+    }
+    details.reverse();
+    Ok(details)
+}
+
+/// What happens when the timeout for a choice runs out, from the choices' `default`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TimeoutDefault {
+    Passage {
+        passage: String,
+    },
+    /// The number of embedded lines, counted as the runner flattens them.
+    Lines {
+        lines: usize,
+    },
+    None,
+}
+impl TimeoutDefault {
+    pub fn from(choices: &RawChoices) -> Self {
+        match &choices.default {
+            ChoiceTarget::PassageName(passage) => Self::Passage {
+                passage: passage.clone(),
+            },
+            ChoiceTarget::Lines(_) => Self::Lines {
+                lines: choices.default.line_len(),
+            },
+            ChoiceTarget::None => Self::None,
+        }
+    }
+}
+
+fn current_passage(runner: &Runner) -> Result<&Passage> {
+    runner
+        .story()
+        .sections
+        .get(runner.namespace())
+        .and_then(|section| section.passages.get(runner.passage()))
+        .ok_or_else(|| error!("Passage '{}' was not found.", runner.passage()))
+}
+
+/// Advances the runner from its current choices as if the timeout ran out.
+pub fn take_timeout_default(runner: &mut Runner) -> Result<Line> {
+    let Some(RawLine::Choices(choices)) = current_raw_line(runner) else {
+        return Err(error!("Current line is not a choice."));
+    };
+    let choices_line = runner.line();
+    let mut bookmark = runner.bookmark().clone();
+    // Resume from the new position instead of the current line.
+    bookmark.next_line = None;
+    match &choices.default {
+        ChoiceTarget::None => return Err(error!("Choices have no default to take on timeout.")),
+        ChoiceTarget::PassageName(passage) => {
+            // Called like the runner calls a choice: returning after the choices, unless they're
+            // followed by a return and there's no `onExit` to run, which makes it a jump.
+            let passage = passage.clone();
+            let return_line = choices_line + choices.line_len();
+            let returns = matches!(
+                flatten(current_passage(runner)?).get(return_line),
+                Some(FlatLine::Return | FlatLine::Raw(RawLine::Return(_)))
+            );
+            let on_exit = runner
+                .story()
+                .get_set_commands(|section| section.on_exit(), runner.bookmark())?;
+            bookmark.set_line(return_line);
+            if !returns || !on_exit.is_empty() {
+                bookmark.stack.push(bookmark.position().clone());
+            }
+            runner.load_bookmark(bookmark)?;
+            runner.goto(passage)?;
+        }
+        ChoiceTarget::Lines(lines) => {
+            let first_line = flatten(current_passage(runner)?)
+                .iter()
+                .position(|line| match (line, lines.first()) {
+                    (FlatLine::Raw(raw), Some(first)) => std::ptr::eq(*raw, first),
+                    _ => false,
+                })
+                .ok_or_else(|| error!("Choices default has no lines."))?;
+            bookmark.set_line(first_line);
+            runner.load_bookmark(bookmark)?;
+        }
+    }
+    runner.next("")
+}
 
 #[no_mangle]
 pub extern "C" fn get_choices() -> usize {
@@ -10,7 +169,7 @@ pub extern "C" fn get_choices() -> usize {
             CHOICES.clear();
             CHOICES.reserve(choices.choices.len());
             for choice in choices {
-                CHOICES.push(FFIStr::from(choice));
+                CHOICES.push(FFIStr::from(choice_label(line_ids(), choice)));
             }
             choices.choices.len()
         } else {
@@ -34,3 +193,41 @@ pub extern "C" fn get_timeout() -> f64 {
         }
     }
 }
+
+fn try_choice_details() -> Result<String> {
+    unsafe {
+        let (Some(runner), Line::Choices(_)) = (RUNNER.as_ref(), &LINE) else {
+            return Err(error!("Called get_choice_details on a non-choice line."));
+        };
+        serde_json::to_string(&build_choice_details(runner, line_ids())?)
+            .map_err(|err| error!("{}", err))
+    }
+}
+/// Returns a JSON list of every choice on the current line, including unavailable ones.
+#[no_mangle]
+pub extern "C" fn get_choice_details() -> FFIStr {
+    unsafe {
+        CHOICE_DETAILS_JSON = json_or_error(try_choice_details());
+        FFIStr::from(&CHOICE_DETAILS_JSON)
+    }
+}
+
+fn try_timeout_default() -> Result<String> {
+    unsafe {
+        let (Some(runner), Line::Choices(_)) = (RUNNER.as_ref(), &LINE) else {
+            return Err(error!("Called get_timeout_default on a non-choice line."));
+        };
+        let Some(RawLine::Choices(choices)) = current_raw_line(runner) else {
+            return Err(error!("Current line is not a choice."));
+        };
+        serde_json::to_string(&TimeoutDefault::from(choices)).map_err(|err| error!("{}", err))
+    }
+}
+/// Returns JSON describing what happens when the current choices time out.
+#[no_mangle]
+pub extern "C" fn get_timeout_default() -> FFIStr {
+    unsafe {
+        TIMEOUT_DEFAULT_JSON = json_or_error(try_timeout_default());
+        FFIStr::from(&TIMEOUT_DEFAULT_JSON)
+    }
+}
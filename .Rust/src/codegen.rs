@@ -1,13 +1,91 @@
-use std::{fs, os::raw::c_char};
+use std::collections::HashMap;
+use std::{fs, os::raw::c_char, path::Path};
 
+use crate::characters::source_character_configs;
+use crate::ffi::json_or_error;
 pub use crate::ffi::FFIStr;
+use crate::sources::load_sources;
+use crate::story::story_path;
 use crate::RUNNER;
 
 use kataru::*;
 
+mod c;
+mod csharp;
+mod json;
+mod symbols;
+mod template;
+mod typescript;
+
+pub use c::CBackend;
+pub use csharp::CSharpBackend;
+pub use json::JsonBackend;
+pub use symbols::{CommandSymbol, Param, StateSymbol, Symbol, Symbols};
+pub use template::{TemplateBackend, DEFAULT_TEMPLATE};
+pub use typescript::TypeScriptBackend;
+
 static mut CODEGEN_WAS_UPDATED: bool = false;
+static mut CODEGEN_CHANGED_FILES: Vec<String> = Vec::new();
+static mut CODEGEN_CHANGED_FILES_JSON: String = String::new();
+static mut CODEGEN_OPTIONS: CodegenOptions = CodegenOptions::new();
+static mut CODEGEN_TEMPLATE: Option<String> = None;
+
+/// Host-configurable layout of the generated code.
+#[derive(Debug, Clone, Default)]
+pub struct CodegenOptions {
+    /// If true, passages and characters are nested in a class per namespace,
+    /// e.g. `Passages.Room1.RedSlimeTalk` instead of `Passages.Room1_RedSlimeTalk`.
+    /// Only the C# backend supports nesting.
+    pub nested: bool,
+    /// If true, each namespace is written to its own file next to the configured path,
+    /// e.g. `Constants.Room1.cs`, which is only rewritten when that namespace changes.
+    /// Only the C# backend supports splitting.
+    pub split: bool,
+    /// Path the story was loaded from.
+    /// If set, doc comments name the file each identifier is declared in.
+    pub story_path: Option<String>,
+}
+impl CodegenOptions {
+    pub const fn new() -> Self {
+        Self {
+            nested: false,
+            split: false,
+            story_path: None,
+        }
+    }
+}
+
+/// Generated source split into an index and a file per namespace,
+/// so that editing one namespace only rewrites that namespace's file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitCodegen {
+    /// Declarations shared by every namespace, written to the configured path.
+    pub index: String,
+    /// `(suffix, source)` for each namespace,
+    /// written next to the index with `suffix` inserted before its extension.
+    pub namespaces: Vec<(String, String)>,
+}
+
+/// Text in every per-namespace file of split output, used to find files left over from removed namespaces.
+pub const SPLIT_MARKER: &str = "part of the split Kataru constants";
+
+/// A target language for the generated constants.
+pub trait Backend {
+    /// Generates a source file declaring every symbol in `symbols`.
+    fn generate(&self, symbols: &Symbols, options: &CodegenOptions) -> Result<String>;
+
+    /// Generates an index and a source file per namespace.
+    fn generate_split(
+        &self,
+        _symbols: &Symbols,
+        _options: &CodegenOptions,
+    ) -> Result<SplitCodegen> {
+        Err(error!("Split codegen output is only supported for C#."))
+    }
+}
 
-/// Generate the constants C# file.
+/// Generate the C# constants file.
+/// Renders the template set by `set_codegen_template` instead if there is one.
 /// Assumes story is already loaded.
 #[no_mangle]
 pub extern "C" fn codegen_consts(path: *const c_char, length: usize) -> FFIStr {
@@ -19,20 +97,126 @@ pub extern "C" fn codegen_consts(path: *const c_char, length: usize) -> FFIStr {
 fn try_codegen_consts(path: &str) -> Result<()> {
     unsafe {
         if let Some(runner) = RUNNER.as_mut() {
-            let source = build_codegen_consts(runner.story())?;
-            if let Ok(old_source) = fs::read_to_string(path) {
-                if source == old_source {
-                    CODEGEN_WAS_UPDATED = false;
-                    return Ok(());
+            let template;
+            let backend: &dyn Backend = match &CODEGEN_TEMPLATE {
+                Some(template_path) => {
+                    template = TemplateBackend::load(template_path)?;
+                    &template
                 }
+                None => &CSharpBackend,
             };
-            if let Err(err) = fs::write(path, &source) {
-                return Err(error!(
-                    "Error writing generated file to '{}': {}",
-                    path, err
-                ));
+            let options = CodegenOptions {
+                story_path: Some(story_path()),
+                ..CODEGEN_OPTIONS.clone()
+            };
+            CODEGEN_CHANGED_FILES = write_codegen(runner.story(), backend, &options, path)?;
+            CODEGEN_WAS_UPDATED = !CODEGEN_CHANGED_FILES.is_empty();
+            Ok(())
+        } else {
+            Err(error!("Story was none."))
+        }
+    }
+}
+
+/// Inserts `suffix` before the extension of `path`, e.g. `Constants.cs` to `Constants.Room1.cs`.
+fn split_path(path: &str, suffix: &str) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    let name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, suffix, ext),
+        None => format!("{}.{}", stem, suffix),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Finds the per-namespace files of split output at `path`, including ones left from earlier runs.
+fn find_split_files(path: &str) -> Vec<String> {
+    let index = Path::new(path);
+    let dir = match index.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let stem = index
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut files: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|file| file.extension() == index.extension())
+        .filter_map(|file| {
+            let file_stem = file.file_stem()?.to_str()?;
+            let suffix = file_stem.strip_prefix(stem)?.strip_prefix('.')?;
+            Some(split_path(path, suffix))
+        })
+        .filter(|file| fs::read_to_string(file).is_ok_and(|source| source.contains(SPLIT_MARKER)))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Writes the constants for `story` to `path`, and a file per namespace if `options.split` is set.
+/// Returns the paths of the files that changed or were removed.
+pub fn write_codegen(
+    story: &Story,
+    backend: &dyn Backend,
+    options: &CodegenOptions,
+    path: &str,
+) -> Result<Vec<String>> {
+    let mut changed = Vec::new();
+    let mut written = Vec::new();
+    if options.split {
+        let split = build_codegen_split(story, backend, options)?;
+        if write_generated(path, &split.index)? {
+            changed.push(path.to_string());
+        }
+        for (suffix, source) in &split.namespaces {
+            let file = split_path(path, suffix);
+            if write_generated(&file, source)? {
+                changed.push(file.clone());
             }
-            CODEGEN_WAS_UPDATED = true;
+            written.push(file);
+        }
+    } else {
+        let source = build_codegen(story, backend, options)?;
+        if write_generated(path, &source)? {
+            changed.push(path.to_string());
+        }
+    }
+
+    for file in find_split_files(path) {
+        if written.contains(&file) {
+            continue;
+        }
+        if let Err(err) = fs::remove_file(&file) {
+            return Err(error!(
+                "Error removing stale generated file '{}': {}",
+                file, err
+            ));
+        }
+        changed.push(file);
+    }
+    Ok(changed)
+}
+
+/// Writes a flat constants file with `backend`, ignoring the template, nesting and splitting settings.
+fn try_codegen_backend(backend: &dyn Backend, path: &str) -> Result<()> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_mut() {
+            let options = CodegenOptions {
+                story_path: Some(story_path()),
+                ..CodegenOptions::new()
+            };
+            let changed = write_codegen(runner.story(), backend, &options, path)?;
+            CODEGEN_WAS_UPDATED |= !changed.is_empty();
+            CODEGEN_CHANGED_FILES.extend(changed);
             Ok(())
         } else {
             Err(error!("Story was none."))
@@ -40,162 +224,247 @@ fn try_codegen_consts(path: &str) -> Result<()> {
     }
 }
 
+/// Generate the constants as a C header.
+#[no_mangle]
+pub extern "C" fn codegen_c_header(path: *const c_char, length: usize) -> FFIStr {
+    let path = FFIStr::to_str(path, length);
+    FFIStr::result(try_codegen_backend(&CBackend, path))
+}
+
+/// Generate the constants as a TypeScript module.
+#[no_mangle]
+pub extern "C" fn codegen_typescript(path: *const c_char, length: usize) -> FFIStr {
+    let path = FFIStr::to_str(path, length);
+    FFIStr::result(try_codegen_backend(&TypeScriptBackend, path))
+}
+
+/// Generate a JSON manifest of the story's symbols.
+#[no_mangle]
+pub extern "C" fn codegen_json(path: *const c_char, length: usize) -> FFIStr {
+    let path = FFIStr::to_str(path, length);
+    FFIStr::result(try_codegen_backend(&JsonBackend, path))
+}
+
+/// Generate the C# handler base classes, with an abstract method per command.
+#[no_mangle]
+pub extern "C" fn codegen_handlers(path: *const c_char, length: usize) -> FFIStr {
+    let path = FFIStr::to_str(path, length);
+    FFIStr::result(try_codegen_handlers(path))
+}
+
+fn try_codegen_handlers(path: &str) -> Result<()> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_mut() {
+            let options = CodegenOptions {
+                story_path: Some(story_path()),
+                ..CodegenOptions::new()
+            };
+            let source = build_codegen_handlers(runner.story(), &options)?;
+            if write_generated(path, &source)? {
+                CODEGEN_CHANGED_FILES.push(path.to_string());
+                CODEGEN_WAS_UPDATED = true;
+            }
+            Ok(())
+        } else {
+            Err(error!("Story was none."))
+        }
+    }
+}
+
+/// Writes `source` to `path` unless the file already contains it.
+/// Returns whether the file was written.
+fn write_generated(path: &str, source: &str) -> Result<bool> {
+    if let Ok(old_source) = fs::read_to_string(path) {
+        if source == old_source {
+            return Ok(false);
+        }
+    };
+    if let Err(err) = fs::write(path, source) {
+        return Err(error!(
+            "Error writing generated file to '{}': {}",
+            path, err
+        ));
+    }
+    Ok(true)
+}
+
 #[no_mangle]
 pub extern "C" fn codegen_was_updated() -> bool {
     unsafe { CODEGEN_WAS_UPDATED }
 }
 
-/// Convert a kataru identifier to a C# varname.
-fn get_varname(name: &str) -> String {
-    if name == "global" {
-        "Global".to_string()
-    } else {
-        name.replace(":", "_")
+/// Gets a JSON list of the files written or removed by the last codegen.
+#[no_mangle]
+pub extern "C" fn codegen_changed_files() -> FFIStr {
+    unsafe {
+        CODEGEN_CHANGED_FILES_JSON = json_or_error(serde_json::to_string(&CODEGEN_CHANGED_FILES));
+        FFIStr::from(&CODEGEN_CHANGED_FILES_JSON)
     }
 }
 
-/// Simultaneously construct vectors of varnames and vardefs.
-fn get_vars_defs<S: AsRef<str>>(names: &[S]) -> (Vec<String>, Vec<String>) {
-    let mut varnames = Vec::with_capacity(names.len());
-    let mut vardefs = Vec::with_capacity(names.len());
+/// Enables or disables writing a file per namespace.
+#[no_mangle]
+pub extern "C" fn set_codegen_split(enabled: bool) {
+    unsafe { CODEGEN_OPTIONS.split = enabled }
+}
+
+/// Enables or disables nesting generated passages and characters by namespace.
+#[no_mangle]
+pub extern "C" fn set_codegen_nested(enabled: bool) {
+    unsafe { CODEGEN_OPTIONS.nested = enabled }
+}
 
-    for name in names {
-        if name.as_ref().is_empty() {
-            continue;
+/// Sets the path of a template to generate code from, or clears it if `path` is empty.
+#[no_mangle]
+pub extern "C" fn set_codegen_template(path: *const c_char, length: usize) {
+    let path = FFIStr::to_str(path, length);
+    unsafe {
+        CODEGEN_TEMPLATE = if path.is_empty() {
+            None
+        } else {
+            Some(path.to_string())
         }
-        let varname = get_varname(name.as_ref());
-        vardefs.push(format!("{} = \"{}\"", varname, name.as_ref()));
-        varnames.push(varname);
     }
+}
 
-    (varnames, vardefs)
+/// Formats `text` as a double quoted C# or TypeScript string literal.
+fn string_literal(text: &str) -> String {
+    quote(text, |c| format!("\\u{:04X}", c as u32))
 }
 
-/// Generates the code for
-/// Make public for test access.
-pub fn build_codegen_consts(story: &Story) -> Result<String> {
-    // Collect all entities that need constants.
-    let mut namespaces = Vec::<&str>::with_capacity(story.sections.len());
-    let mut passages = Vec::<String>::with_capacity(story.sections.len());
-    let mut characters = Vec::<String>::with_capacity(story.sections.len());
-
-    // Keep track of namespace boundaries for passages and characters.
-    // The last passage index used for this namespace`passage_bounds[namespace_enum]`.
-
-    for (namespace, section) in &story.sections {
-        // For global namespace, don't add global to the sorted list.
-        // And don't prepend the namespace.
-        if namespace == kataru::GLOBAL {
-            for character in section.config.characters.keys() {
-                characters.push(character.to_string());
-            }
-            for passage_name in section.passages.keys() {
-                passages.push(passage_name.to_string());
-            }
+/// Formats `text` as a double quoted C string literal.
+/// Control characters are escaped by their UTF-8 bytes in octal, since hex escapes don't end after two digits.
+fn c_string_literal(text: &str) -> String {
+    quote(text, |c| {
+        let mut bytes = [0; 4];
+        c.encode_utf8(&mut bytes)
+            .bytes()
+            .map(|byte| format!("\\{:03o}", byte))
+            .collect()
+    })
+}
+
+/// Formats `number` as a floating point literal, naming NaN and positive infinity with `nan` and `infinity`.
+/// Large and small values use exponent notation, since they would otherwise be too long for an integer literal.
+fn number_literal(number: f64, nan: &str, infinity: &str) -> String {
+    if number.is_nan() {
+        nan.to_string()
+    } else if number.is_infinite() {
+        let sign = if number < 0.0 { "-" } else { "" };
+        format!("{}{}", sign, infinity)
+    } else {
+        let literal = number.to_string();
+        if literal.len() > 15 {
+            format!("{:e}", number)
         } else {
-            namespaces.push(namespace);
-            for character in section.config.characters.keys() {
-                characters.push(format!("{}:{}", namespace, character));
-            }
-            for passage_name in section.passages.keys() {
-                passages.push(format!("{}:{}", namespace, passage_name));
+            literal
+        }
+    }
+}
+
+/// Quotes `text`, escaping control characters and line separators with `escape`.
+fn quote(text: &str, escape: fn(char) -> String) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+    for c in text.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '"' => literal.push_str("\\\""),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() || matches!(c, '\u{2028}' | '\u{2029}') => {
+                literal.push_str(&escape(c))
             }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Maps each character that isn't valid in an identifier to `_`,
+/// and prefixes names starting with a digit with `_`.
+fn sanitize(name: &str, valid: fn(char) -> bool) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if valid(c) { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// Checks that no two `(identifier, name)` entries share an identifier, and that none use a `reserved` one.
+fn check_collisions<'a, I>(language: &str, kind: &str, reserved: &[&str], entries: I) -> Result<()>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for (identifier, name) in entries {
+        if reserved.contains(&identifier) {
+            return Err(error!(
+                "{} '{}' generates the reserved {} identifier '{}'.",
+                kind, name, language, identifier
+            ));
+        }
+        if let Some(other) = seen.insert(identifier, name) {
+            return Err(error!(
+                "{}s '{}' and '{}' both generate the {} identifier '{}'.",
+                kind, other, name, language, identifier
+            ));
         }
     }
+    Ok(())
+}
+
+/// Capitalizes the first letter of `text`, for use in error messages.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Generates the C# constants file with the default, flat layout.
+/// Make public for test access.
+pub fn build_codegen_consts(story: &Story) -> Result<String> {
+    build_codegen(story, &CSharpBackend, &CodegenOptions::new())
+}
 
-    namespaces.sort();
-    passages.sort();
-    characters.sort();
-
-    let (namespace_vars, namespace_defs) = get_vars_defs(&namespaces);
-    let (passage_vars, passage_defs) = get_vars_defs(&passages);
-    let (character_vars, character_defs) = get_vars_defs(&characters);
-
-    let separator = ",\n            ";
-    let source = format!(
-        r###"/// DO NOT EDIT.
-/// This file was autogenerated by Kataru based on your scripts.
-
-namespace Kataru
-{{
-    /// <summary>
-    /// Autogenerated class containing all namespace strings.
-    /// </summary>
-    public static class Namespaces
-    {{
-        public const string Global = "{global}",
-            {namespace_defs};
-
-        /// <summary>
-        /// Get all namespace names.
-        /// </summary>
-        public static string[] All() => all;
-
-        private static readonly string[] all = {{
-            Global,
-            {namespace_vars}
-        }};
-    }}
-
-    /// <summary>
-    /// Autogenerated class containing all passage strings.
-    /// </summary>
-    public static class Passages
-    {{
-        public const string None = "None",
-            {passage_defs};
-
-        /// <summary>
-        /// Get all passage names.
-        /// </summary>
-        public static string[] All() => all;
-
-        /// <summary>
-        /// Get all passage names in a given namespace.
-        /// This requires a linear search over all passage names, so don't do this at runtime.
-        /// </summary>
-        public static string[] InNamespace(string @namespace) => NamespaceUtils.FilterByNamespace(all, @namespace);
-
-        private static readonly string[] all = {{
-            None,
-            {passage_vars}
-        }};
-    }}
-
-    /// <summary>
-    /// Autogenerated class containing all character strings.
-    /// </summary>
-    public static partial class Characters
-    {{
-        public const string None = "None",
-            {character_defs};
-
-        /// <summary>
-        /// Get all character names.
-        /// </summary>
-        public static string[] All() => all;
-
-        /// <summary>
-        /// Get all character names in a given namespace.
-        /// This requires a linear search over all passage names, so don't do this at runtime.
-        /// </summary>
-        public static string[] InNamespace(string @namespace) => NamespaceUtils.FilterByNamespace(all, @namespace);
-
-        private static readonly string[] all = {{
-            None,
-            {character_vars}
-        }};
-    }}
-}}
-"###,
-        global = kataru::GLOBAL,
-        namespace_defs = namespace_defs.join(separator),
-        namespace_vars = namespace_vars.join(separator),
-        passage_defs = passage_defs.join(separator),
-        passage_vars = passage_vars.join(separator),
-        character_defs = character_defs.join(separator),
-        character_vars = character_vars.join(separator),
-    );
-
-    Ok(source)
+/// Generates the constants for `backend`, split into an index and a file per namespace.
+pub fn build_codegen_split(
+    story: &Story,
+    backend: &dyn Backend,
+    options: &CodegenOptions,
+) -> Result<SplitCodegen> {
+    backend.generate_split(&extract_symbols(story, options), options)
+}
+
+/// Generates the constants file for `backend`.
+pub fn build_codegen(
+    story: &Story,
+    backend: &dyn Backend,
+    options: &CodegenOptions,
+) -> Result<String> {
+    backend.generate(&extract_symbols(story, options), options)
+}
+
+/// Generates the C# handler base classes.
+pub fn build_codegen_handlers(story: &Story, options: &CodegenOptions) -> Result<String> {
+    csharp::build_handlers(&extract_symbols(story, options))
+}
+
+/// Extracts the symbols of `story`.
+/// If `options.story_path` is set, their source files and raw character configs are read from it.
+fn extract_symbols(story: &Story, options: &CodegenOptions) -> Symbols {
+    let mut symbols = Symbols::extract(story);
+    if let Some(story_path) = &options.story_path {
+        let sources = load_sources(story_path);
+        symbols.locate_sources(&sources);
+        symbols.describe_characters(&source_character_configs(&sources));
+    }
+    symbols
 }
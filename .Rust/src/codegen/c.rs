@@ -0,0 +1,195 @@
+use super::{
+    c_string_literal, check_collisions, number_literal, sanitize, Backend, CodegenOptions,
+};
+use super::{CommandSymbol, Symbol, Symbols};
+use crate::namespaces::qualify;
+use kataru::*;
+
+/// Generates a C header of string macros and typed command parameter structs.
+pub struct CBackend;
+
+/// C keywords, which get a `_` suffix when used as struct fields.
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long",
+    "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "true", "typedef", "union", "unsigned", "void", "volatile", "while",
+];
+
+/// Convert a kataru identifier to the suffix of a C macro, e.g. `Room1:RedSlime` to `ROOM1_REDSLIME`.
+fn get_macro_name(name: &str) -> String {
+    sanitize(name, |c| c.is_ascii_alphanumeric() || c == '_').to_ascii_uppercase()
+}
+
+/// Convert a parameter name to a C struct field.
+fn get_field_name(name: &str) -> String {
+    let mut field = sanitize(name, |c| c.is_ascii_alphanumeric() || c == '_');
+    if C_KEYWORDS.contains(&field.as_str()) {
+        field.push('_');
+    }
+    field
+}
+
+/// Convert a declared command to a C macro suffix, e.g. `Room1:$character.Wave` to `ROOM1_CHARACTER_WAVE`.
+fn get_command_name(command: &CommandSymbol) -> String {
+    let name = if command.character {
+        format!("Character_{}", command.base_name)
+    } else {
+        command.base_name.clone()
+    };
+    get_macro_name(&qualify(&command.symbol.namespace, &name))
+}
+
+/// Gets the C declaration prefix and initializer for a parameter's default value.
+fn get_type_literal(value: &Value) -> (&'static str, String) {
+    match value {
+        Value::String(string) => ("const char *", c_string_literal(string)),
+        Value::Number(number) => ("double ", number_literal(*number, "NAN", "INFINITY")),
+        Value::Bool(boolean) => ("bool ", boolean.to_string()),
+    }
+}
+
+/// Generates a `#define` for each name, checking that no two names generate the same macro.
+fn get_defines<'a, I>(kind: &str, prefix: &str, names: I) -> Result<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let entries: Vec<(String, &str)> = names
+        .into_iter()
+        .map(|name| (get_macro_name(name), name))
+        .collect();
+    check_collisions(
+        "C",
+        kind,
+        &[],
+        entries
+            .iter()
+            .map(|(macro_name, name)| (macro_name.as_str(), *name)),
+    )?;
+    Ok(entries
+        .iter()
+        .map(|(macro_name, name)| {
+            format!(
+                "#define KATARU_{}_{} {}\n",
+                prefix,
+                macro_name,
+                c_string_literal(name)
+            )
+        })
+        .collect())
+}
+
+/// Generates a typed struct for the parameters of a command and a macro initializing it with their defaults.
+fn get_params_struct(command: &CommandSymbol, name: &str) -> Result<String> {
+    if command.params.is_empty() {
+        return Ok(String::new());
+    }
+    let fields: Vec<String> = command
+        .params
+        .iter()
+        .map(|param| get_field_name(&param.name))
+        .collect();
+    check_collisions(
+        "C",
+        &format!("Parameter of command '{}'", command.symbol.qualified),
+        &[],
+        fields
+            .iter()
+            .map(String::as_str)
+            .zip(command.params.iter().map(|param| param.name.as_str())),
+    )?;
+    let mut members = String::new();
+    let mut defaults = Vec::with_capacity(fields.len());
+    for (field, param) in fields.iter().zip(&command.params) {
+        let (param_type, literal) = get_type_literal(&param.default);
+        members.push_str(&format!("    {}{};\n", param_type, field));
+        defaults.push(format!(".{} = {}", field, literal));
+    }
+    Ok(format!(
+        r###"
+/* Parameters of the `{command}` command. */
+typedef struct {{
+{members}}} kataru_{lower}_params;
+
+#define KATARU_{name}_PARAMS_DEFAULT {{ {defaults} }}
+"###,
+        command = command.symbol.qualified,
+        members = members,
+        lower = name.to_ascii_lowercase(),
+        name = name,
+        defaults = defaults.join(", "),
+    ))
+}
+
+fn qualified(symbols: &[Symbol]) -> impl Iterator<Item = &str> {
+    symbols.iter().map(|symbol| symbol.qualified.as_str())
+}
+
+impl Backend for CBackend {
+    fn generate(&self, symbols: &Symbols, _options: &CodegenOptions) -> Result<String> {
+        let commands: Vec<(String, &CommandSymbol)> = symbols
+            .commands
+            .iter()
+            .map(|command| (get_command_name(command), command))
+            .collect();
+        check_collisions(
+            "C",
+            "Command",
+            &[],
+            commands
+                .iter()
+                .map(|(name, command)| (name.as_str(), command.symbol.qualified.as_str())),
+        )?;
+        let mut command_defines = String::new();
+        let mut command_params = String::new();
+        for (name, command) in &commands {
+            command_defines.push_str(&format!(
+                "#define KATARU_COMMAND_{} {}\n",
+                name,
+                c_string_literal(&command.symbol.qualified)
+            ));
+            command_params.push_str(&get_params_struct(command, name)?);
+        }
+
+        Ok(format!(
+            r###"/* DO NOT EDIT.
+ * This file was autogenerated by Kataru based on your scripts.
+ */
+#ifndef KATARU_CONSTANTS_H
+#define KATARU_CONSTANTS_H
+
+#include <math.h>
+#include <stdbool.h>
+
+/* Namespaces */
+{namespaces}
+/* Passages */
+{passages}
+/* Characters */
+{characters}
+/* Commands */
+{command_defines}
+/* State variables */
+{state}{command_params}
+#endif /* KATARU_CONSTANTS_H */
+"###,
+            namespaces = get_defines(
+                "Namespace",
+                "NAMESPACE",
+                symbols.namespaces.iter().map(String::as_str)
+            )?,
+            passages = get_defines("Passage", "PASSAGE", qualified(&symbols.passages))?,
+            characters = get_defines("Character", "CHARACTER", qualified(&symbols.characters))?,
+            command_defines = command_defines,
+            state = get_defines(
+                "State variable",
+                "STATE",
+                symbols
+                    .state
+                    .iter()
+                    .map(|var| var.symbol.qualified.as_str())
+            )?,
+            command_params = command_params,
+        ))
+    }
+}
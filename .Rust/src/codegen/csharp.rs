@@ -0,0 +1,1025 @@
+use super::SPLIT_MARKER;
+use super::{capitalize, check_collisions, number_literal, sanitize, string_literal};
+use super::{Backend, CodegenOptions};
+use super::{CommandSymbol, Param, SplitCodegen, StateSymbol, Symbol, Symbols};
+use crate::namespaces::qualify;
+use kataru::*;
+
+/// Generates C# classes of constants, typed command parameters and state accessors.
+pub struct CSharpBackend;
+
+/// C# keywords, which have to be escaped with `@` to be used as identifiers.
+const CSHARP_KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Members of the generated classes that names can't be mapped onto.
+pub(super) const LIST_MEMBERS: &[&str] = &["None", "All", "InNamespace", "all"];
+
+/// Members of `Handler` that generated handler methods can't be named.
+const HANDLER_MEMBERS: &[&str] = &[
+    "Name",
+    "OnEnable",
+    "OnDisable",
+    "OnDialogue",
+    "CommandDelegates",
+    "CharacterDelegates",
+    "GetActionsForAttribute",
+];
+
+/// Convert a kataru identifier to a C# varname, e.g. `Room1:RedSlime` to `Room1_RedSlime`.
+pub(super) fn get_varname(name: &str) -> String {
+    if name == GLOBAL {
+        return "Global".to_string();
+    }
+    let mut varname = sanitize(name, |c| c.is_alphanumeric() || c == '_');
+    if CSHARP_KEYWORDS.contains(&varname.as_str()) {
+        varname.insert(0, '@');
+    }
+    varname
+}
+
+/// Checks that no two names generate the same C# identifier.
+pub(super) fn check_varnames<'a, I>(kind: &str, reserved: &[&str], entries: I) -> Result<()>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    check_collisions("C#", kind, reserved, entries)
+}
+
+/// Simultaneously construct vectors of varnames and vardefs.
+fn get_vars_defs<S: AsRef<str>>(
+    kind: &str,
+    names: &[S],
+    reserved: &[&str],
+) -> Result<(Vec<String>, Vec<String>)> {
+    let mut varnames = Vec::with_capacity(names.len());
+    let mut vardefs = Vec::with_capacity(names.len());
+
+    for name in names {
+        if name.as_ref().is_empty() {
+            continue;
+        }
+        let varname = get_varname(name.as_ref());
+        vardefs.push(format!("{} = {}", varname, string_literal(name.as_ref())));
+        varnames.push(varname);
+    }
+    check_varnames(
+        kind,
+        reserved,
+        varnames.iter().map(String::as_str).zip(
+            names
+                .iter()
+                .map(AsRef::as_ref)
+                .filter(|name| !name.is_empty()),
+        ),
+    )?;
+
+    Ok((varnames, vardefs))
+}
+
+/// Escapes text for use in an XML doc comment.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Gets the escaped lines of a symbol's doc comment, starting with the file declaring it.
+pub(super) fn get_summary(symbol: &Symbol) -> Vec<String> {
+    let source = symbol
+        .source
+        .iter()
+        .map(|source| format!("Declared in {}.", source));
+    source
+        .chain(symbol.doc.iter().cloned())
+        .flat_map(|doc| doc.lines().map(xml_escape).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Generates the `<summary>` doc comment of `symbol`, if there's anything to say about it.
+fn get_doc(symbol: &Symbol, indent: &str) -> String {
+    let summary = get_summary(symbol);
+    if summary.is_empty() {
+        return String::new();
+    }
+    let mut doc = format!("{}/// <summary>\n", indent);
+    for line in summary {
+        doc.push_str(&format!("{}/// {}\n", indent, line));
+    }
+    doc.push_str(&format!("{}/// </summary>\n", indent));
+    doc
+}
+
+/// Generates a documented constant for each `(varname, symbol)` entry, each preceded by a blank line.
+fn get_consts(entries: &[(String, &Symbol)], indent: &str) -> String {
+    entries
+        .iter()
+        .map(|(varname, symbol)| {
+            format!(
+                "\n{}{}public const string {} = {};\n",
+                get_doc(symbol, indent),
+                indent,
+                varname,
+                string_literal(&symbol.qualified)
+            )
+        })
+        .collect()
+}
+
+/// Convert a declared command to a C# varname, e.g. `Room1:$character.Wave` to `Room1_Character_Wave`.
+pub(super) fn get_command_varname(command: &CommandSymbol) -> String {
+    let name = if command.character {
+        format!("Character_{}", command.base_name)
+    } else {
+        command.base_name.clone()
+    };
+    get_varname(&qualify(&command.symbol.namespace, &name))
+}
+
+/// Gets the C# type and literal for a parameter's default value.
+pub(super) fn get_type_literal(value: &Value) -> (&'static str, String) {
+    match value {
+        Value::String(string) => ("string", string_literal(string)),
+        Value::Number(number) => (
+            "double",
+            number_literal(*number, "double.NaN", "double.PositiveInfinity"),
+        ),
+        Value::Bool(boolean) => ("bool", boolean.to_string()),
+    }
+}
+
+/// Generates a typed struct for the parameters of a command, with their defaults.
+fn get_params_struct(command: &CommandSymbol, varname: &str) -> Result<Option<String>> {
+    if command.params.is_empty() {
+        return Ok(None);
+    }
+    let params: Vec<(String, &Param)> = command
+        .params
+        .iter()
+        .map(|param| (get_varname(&param.name), param))
+        .collect();
+    check_varnames(
+        &format!("Parameter of command '{}'", command.symbol.qualified),
+        &["Default"],
+        params
+            .iter()
+            .map(|(field, param)| (field.as_str(), param.name.as_str())),
+    )?;
+    let mut fields = Vec::with_capacity(params.len());
+    let mut defaults = Vec::with_capacity(params.len());
+    for (field, param) in params {
+        let (param_type, literal) = get_type_literal(&param.default);
+        fields.push(format!("public {} {};", param_type, field));
+        defaults.push(format!("{} = {}", field, literal));
+    }
+    Ok(Some(format!(
+        r###"
+    /// <summary>
+    /// Autogenerated parameters of the `{command}` command.
+    /// </summary>
+    public struct {varname}Params
+    {{
+        {fields}
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly {varname}Params Default = new {varname}Params
+        {{
+            {defaults}
+        }};
+    }}
+"###,
+        command = command.symbol.qualified,
+        varname = varname,
+        fields = fields.join("\n        "),
+        defaults = defaults.join(",\n            "),
+    )))
+}
+
+/// Generates a class listing `symbols` with a flat `Namespace_Name` constant for each.
+fn get_flat_class(modifiers: &str, class: &str, kind: &str, symbols: &[Symbol]) -> Result<String> {
+    let entries: Vec<(String, &Symbol)> = symbols
+        .iter()
+        .map(|symbol| (get_varname(&symbol.qualified), symbol))
+        .collect();
+    check_varnames(
+        &capitalize(kind),
+        LIST_MEMBERS,
+        entries
+            .iter()
+            .map(|(varname, symbol)| (varname.as_str(), symbol.qualified.as_str())),
+    )?;
+    let mut vars = vec!["None"];
+    vars.extend(entries.iter().map(|(varname, _)| varname.as_str()));
+    Ok(format!(
+        r###"
+    /// <summary>
+    /// Autogenerated class containing all {kind} strings.
+    /// </summary>
+    public {modifiers} class {class}
+    {{
+        public const string None = "None";
+{consts}
+        /// <summary>
+        /// Get all {kind} names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all {kind} names in a given namespace.
+        /// This requires a linear search over all passage names, so don't do this at runtime.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) => NamespaceUtils.FilterByNamespace(all, @namespace);
+
+        private static readonly string[] all = {{
+            {vars}
+        }};
+    }}
+"###,
+        modifiers = modifiers,
+        class = class,
+        kind = kind,
+        consts = get_consts(&entries, "        "),
+        vars = vars.join(",\n            "),
+    ))
+}
+
+/// Generates the nested class of constants for `entries` in `namespace`.
+fn get_namespace_class(
+    kind: &str,
+    namespace: &str,
+    class_name: &str,
+    entries: &[(String, &Symbol)],
+) -> String {
+    let vars: Vec<&str> = entries
+        .iter()
+        .map(|(varname, _)| varname.as_str())
+        .collect();
+    format!(
+        r###"
+        /// <summary>
+        /// Autogenerated class containing all {kind} strings in the `{namespace}` namespace.
+        /// </summary>
+        public static class {class_name}
+        {{{consts}
+            /// <summary>
+            /// Get all {kind} names in this namespace.
+            /// </summary>
+            public static string[] All() => all;
+
+            private static readonly string[] all = {{
+                {vars}
+            }};
+        }}
+"###,
+        kind = kind,
+        namespace = namespace,
+        class_name = class_name,
+        consts = get_consts(entries, "            "),
+        vars = vars.join(",\n                "),
+    )
+}
+
+/// Generates a class listing `symbols` with a nested class of constants per namespace,
+/// along with precomputed lists of the names in each namespace.
+fn get_nested_class(
+    modifiers: &str,
+    class: &str,
+    kind: &str,
+    symbols: &[Symbol],
+) -> Result<String> {
+    // Group the names by namespace, with the global namespace first.
+    let mut namespaces: Vec<(&str, Vec<(String, &Symbol)>)> = Vec::new();
+    for symbol in symbols {
+        let namespace = symbol.namespace.as_str();
+        let entry = (get_varname(&symbol.name), symbol);
+        match namespaces.iter_mut().find(|(other, _)| *other == namespace) {
+            Some((_, entries)) => entries.push(entry),
+            None => namespaces.push((namespace, vec![entry])),
+        }
+    }
+    namespaces.sort_by_key(|(namespace, _)| (*namespace != GLOBAL, *namespace));
+    let class_names: Vec<String> = namespaces
+        .iter()
+        .map(|(namespace, _)| get_varname(namespace))
+        .collect();
+    check_varnames(
+        "Namespace",
+        &["None", "All", "InNamespace", "all", "byNamespace"],
+        class_names
+            .iter()
+            .map(String::as_str)
+            .zip(namespaces.iter().map(|(namespace, _)| *namespace)),
+    )?;
+
+    let mut nested_classes = String::new();
+    let mut all = vec!["None".to_string()];
+    let mut by_namespace = Vec::with_capacity(namespaces.len());
+    for ((namespace, entries), class_name) in namespaces.iter().zip(&class_names) {
+        check_varnames(
+            &capitalize(kind),
+            &["All", "all", class_name],
+            entries
+                .iter()
+                .map(|(varname, symbol)| (varname.as_str(), symbol.qualified.as_str())),
+        )?;
+        all.extend(
+            entries
+                .iter()
+                .map(|(varname, _)| format!("{}.{}", class_name, varname)),
+        );
+        by_namespace.push(format!(
+            "{{ {}, {}.All() }}",
+            string_literal(namespace),
+            class_name
+        ));
+        nested_classes.push_str(&get_namespace_class(kind, namespace, class_name, entries));
+    }
+
+    Ok(format!(
+        r###"
+    /// <summary>
+    /// Autogenerated class containing all {kind} strings, nested by namespace.
+    /// </summary>
+    public {modifiers} class {class}
+    {{
+        public const string None = "None";
+
+        /// <summary>
+        /// Get all {kind} names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all {kind} names in a given namespace, which are precomputed.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) =>
+            byNamespace.TryGetValue(@namespace, out var names) ? names : new string[0];
+{nested_classes}
+        private static readonly string[] all = {{
+            {all}
+        }};
+
+        private static readonly System.Collections.Generic.Dictionary<string, string[]> byNamespace =
+            new System.Collections.Generic.Dictionary<string, string[]>
+            {{
+                {by_namespace}
+            }};
+    }}
+"###,
+        modifiers = modifiers,
+        class = class,
+        kind = kind,
+        nested_classes = nested_classes,
+        all = all.join(",\n            "),
+        by_namespace = by_namespace.join(",\n                "),
+    ))
+}
+
+/// Generates the class listing every namespace other than the global namespace.
+fn get_namespaces_class(namespaces: &[&str]) -> Result<String> {
+    let (namespace_vars, namespace_defs) =
+        get_vars_defs("Namespace", namespaces, &["Global", "All", "all"])?;
+    let separator = ",\n            ";
+    Ok(format!(
+        r###"
+    /// <summary>
+    /// Autogenerated class containing all namespace strings.
+    /// </summary>
+    public static class Namespaces
+    {{
+        public const string Global = "{global}",
+            {namespace_defs};
+
+        /// <summary>
+        /// Get all namespace names.
+        /// </summary>
+        public static string[] All() => all;
+
+        private static readonly string[] all = {{
+            Global,
+            {namespace_vars}
+        }};
+    }}
+"###,
+        global = GLOBAL,
+        namespace_defs = namespace_defs.join(separator),
+        namespace_vars = namespace_vars.join(separator),
+    ))
+}
+
+/// Generates the typed accessor of a state variable.
+fn get_state_property(varname: &str, var: &StateSymbol) -> String {
+    let (var_type, _) = get_type_literal(&var.value);
+    format!(
+        r###"
+
+{doc}        public static {var_type} {varname}
+        {{
+            get => Runner.GetState<{var_type}>(Keys.{varname});
+            set => Runner.SetState(Keys.{varname}, value);
+        }}"###,
+        doc = get_doc(&var.symbol, "        "),
+        var_type = var_type,
+        varname = varname,
+    )
+}
+
+/// Gets the name of the array listing the names of `namespace` in a split, flat class.
+fn get_namespace_array(namespace: &str) -> String {
+    format!("all_{}", get_varname(namespace).trim_start_matches('@'))
+}
+
+/// Generates the part of a split list class that is shared by every namespace,
+/// collecting the names declared in each namespace's file.
+fn get_split_index(class: &str, kind: &str, namespaces: &[&str], nested: bool) -> String {
+    let arrays: Vec<String> = namespaces
+        .iter()
+        .map(|namespace| {
+            if nested {
+                format!("{}.All()", get_varname(namespace))
+            } else {
+                get_namespace_array(namespace)
+            }
+        })
+        .collect();
+    let by_namespace: Vec<String> = namespaces
+        .iter()
+        .zip(&arrays)
+        .map(|(namespace, array)| format!("{{ {}, {} }}", string_literal(namespace), array))
+        .collect();
+    let add_names: String = arrays
+        .iter()
+        .map(|array| format!("\n            names.AddRange({});", array))
+        .collect();
+    format!(
+        r###"
+    /// <summary>
+    /// Autogenerated class containing all {kind} strings.
+    /// The {kind}s of each namespace are declared in that namespace's file.
+    /// </summary>
+    public static partial class {class}
+    {{
+        public const string None = "None";
+
+        /// <summary>
+        /// Get all {kind} names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all {kind} names in a given namespace, which are precomputed.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) =>
+            byNamespace.TryGetValue(@namespace, out var names) ? names : new string[0];
+
+        private static readonly string[] all;
+
+        private static readonly System.Collections.Generic.Dictionary<string, string[]> byNamespace;
+
+        // Built in the static constructor, which runs after the initializers in every namespace's file.
+        static {class}()
+        {{
+            byNamespace = new System.Collections.Generic.Dictionary<string, string[]>
+            {{
+                {by_namespace}
+            }};
+            var names = new System.Collections.Generic.List<string> {{ None }};{add_names}
+            all = names.ToArray();
+        }}
+    }}
+"###,
+        kind = kind,
+        class = class,
+        by_namespace = by_namespace.join(",\n                "),
+        add_names = add_names,
+    )
+}
+
+/// Generates the part of a split list class declaring the `(varname, symbol)` entries of one namespace.
+fn get_split_part(
+    class: &str,
+    kind: &str,
+    namespace: &str,
+    entries: &[(String, &Symbol)],
+    nested: bool,
+) -> String {
+    let members = if nested {
+        get_namespace_class(kind, namespace, &get_varname(namespace), entries)
+    } else {
+        let vars: Vec<&str> = entries
+            .iter()
+            .map(|(varname, _)| varname.as_str())
+            .collect();
+        format!(
+            r###"{consts}
+        private static readonly string[] {array} = {{
+            {vars}
+        }};
+"###,
+            consts = get_consts(entries, "        "),
+            array = get_namespace_array(namespace),
+            vars = vars.join(",\n            "),
+        )
+    };
+    format!(
+        r###"
+    public static partial class {class}
+    {{
+{members}    }}
+"###,
+        class = class,
+        members = members.trim_start_matches('\n'),
+    )
+}
+
+/// Wraps the classes of a split file in the `Kataru` namespace.
+fn get_split_file(note: &str, classes: &str) -> String {
+    format!(
+        r###"/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+/// {note}
+
+namespace Kataru
+{{{classes}}}
+"###,
+        note = note,
+        classes = classes,
+    )
+}
+
+impl Backend for CSharpBackend {
+    fn generate(&self, symbols: &Symbols, options: &CodegenOptions) -> Result<String> {
+        let namespaces: Vec<&str> = symbols.namespaces.iter().map(String::as_str).collect();
+        let get_class = if options.nested {
+            get_nested_class
+        } else {
+            get_flat_class
+        };
+        let passages_class = get_class("static", "Passages", "passage", &symbols.passages)?;
+        let characters_class = get_class(
+            "static partial",
+            "Characters",
+            "character",
+            &symbols.characters,
+        )?;
+        let commands: Vec<(String, &CommandSymbol)> = symbols
+            .commands
+            .iter()
+            .map(|command| (get_command_varname(command), command))
+            .collect();
+        check_varnames(
+            "Command",
+            LIST_MEMBERS,
+            commands
+                .iter()
+                .map(|(varname, command)| (varname.as_str(), command.symbol.qualified.as_str())),
+        )?;
+        let state: Vec<(String, &StateSymbol)> = symbols
+            .state
+            .iter()
+            .map(|var| (get_varname(&var.symbol.qualified), var))
+            .collect();
+        check_varnames(
+            "State variable",
+            &["None", "Keys"],
+            state
+                .iter()
+                .map(|(varname, var)| (varname.as_str(), var.symbol.qualified.as_str())),
+        )?;
+
+        let separator = ",\n            ";
+        let mut state_defs = vec!["None = \"None\"".to_string()];
+        let mut state_properties = Vec::with_capacity(state.len());
+        for (varname, var) in &state {
+            state_defs.push(format!(
+                "{} = {}",
+                varname,
+                string_literal(&var.symbol.qualified)
+            ));
+            state_properties.push(get_state_property(varname, var));
+        }
+        let mut command_consts = Vec::with_capacity(commands.len());
+        let mut command_vars = vec!["None".to_string()];
+        let mut command_params = String::new();
+        for (varname, command) in &commands {
+            command_consts.push((varname.clone(), &command.symbol));
+            command_vars.push(varname.clone());
+            command_params.extend(get_params_struct(command, varname)?);
+        }
+        let source = format!(
+            r###"/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+
+namespace Kataru
+{{{namespaces_class}{passages_class}{characters_class}
+    /// <summary>
+    /// Autogenerated class containing all command strings.
+    /// Character commands are declared as `$character.Command`, and called as `Character.Command`.
+    /// </summary>
+    public static class Commands
+    {{
+        public const string None = "None";
+{command_consts}
+        /// <summary>
+        /// Get all command names.
+        /// </summary>
+        public static string[] All() => all;
+
+        private static readonly string[] all = {{
+            {command_vars}
+        }};
+    }}
+
+    /// <summary>
+    /// Autogenerated class containing typed accessors for all state variables.
+    /// The type of each variable is inferred from its initial value.
+    /// </summary>
+    public static class State
+    {{
+        /// <summary>
+        /// Autogenerated class containing all state variable names.
+        /// </summary>
+        public static class Keys
+        {{
+            public const string {state_defs};
+        }}{state_properties}
+    }}
+{command_params}}}
+"###,
+            namespaces_class = get_namespaces_class(&namespaces)?,
+            passages_class = passages_class,
+            characters_class = characters_class,
+            command_consts = get_consts(&command_consts, "        "),
+            command_vars = command_vars.join(separator),
+            command_params = command_params,
+            state_defs = state_defs.join(",\n                "),
+            state_properties = state_properties.concat(),
+        );
+
+        Ok(source)
+    }
+
+    fn generate_split(&self, symbols: &Symbols, options: &CodegenOptions) -> Result<SplitCodegen> {
+        // Generating the single file checks every name for collisions.
+        self.generate(symbols, options)?;
+
+        let mut namespaces: Vec<&str> = vec![GLOBAL];
+        namespaces.extend(symbols.namespaces.iter().map(String::as_str));
+        let arrays: Vec<String> = namespaces
+            .iter()
+            .map(|namespace| get_namespace_array(namespace))
+            .collect();
+        let mut reserved: Vec<&str> = arrays.iter().map(String::as_str).collect();
+        reserved.push("byNamespace");
+
+        // `(class, kind, entries by namespace)` for each split list class.
+        let mut lists = Vec::new();
+        for (class, kind, list) in [
+            ("Passages", "passage", &symbols.passages),
+            ("Characters", "character", &symbols.characters),
+        ] {
+            let entries: Vec<(String, &Symbol)> = list
+                .iter()
+                .map(|symbol| {
+                    let varname = if options.nested {
+                        get_varname(&symbol.name)
+                    } else {
+                        get_varname(&symbol.qualified)
+                    };
+                    (varname, symbol)
+                })
+                .collect();
+            lists.push((class, kind, entries, options.nested));
+        }
+        let commands: Vec<(String, &Symbol)> = symbols
+            .commands
+            .iter()
+            .map(|command| (get_command_varname(command), &command.symbol))
+            .collect();
+        lists.push(("Commands", "command", commands, false));
+
+        let mut index = get_namespaces_class(
+            &symbols
+                .namespaces
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+        )?;
+        let mut files: Vec<String> = vec![String::new(); namespaces.len()];
+        for (class, kind, entries, nested) in &lists {
+            if !nested {
+                check_varnames(
+                    &capitalize(kind),
+                    &reserved,
+                    entries
+                        .iter()
+                        .map(|(varname, symbol)| (varname.as_str(), symbol.qualified.as_str())),
+                )?;
+            }
+            let mut declared = Vec::new();
+            for (namespace, file) in namespaces.iter().zip(files.iter_mut()) {
+                let namespace_entries: Vec<(String, &Symbol)> = entries
+                    .iter()
+                    .filter(|(_, symbol)| symbol.namespace == *namespace)
+                    .cloned()
+                    .collect();
+                if namespace_entries.is_empty() {
+                    continue;
+                }
+                declared.push(*namespace);
+                file.push_str(&get_split_part(
+                    class,
+                    kind,
+                    namespace,
+                    &namespace_entries,
+                    *nested,
+                ));
+            }
+            index.push_str(&get_split_index(class, kind, &declared, *nested));
+        }
+
+        index.push_str(
+            r###"
+    /// <summary>
+    /// Autogenerated class containing typed accessors for all state variables.
+    /// The type of each variable is inferred from its initial value.
+    /// The variables of each namespace are declared in that namespace's file.
+    /// </summary>
+    public static partial class State
+    {
+        /// <summary>
+        /// Autogenerated class containing all state variable names.
+        /// </summary>
+        public static partial class Keys
+        {
+            public const string None = "None";
+        }
+    }
+"###,
+        );
+        for (namespace, file) in namespaces.iter().zip(files.iter_mut()) {
+            let state: Vec<(String, &StateSymbol)> = symbols
+                .state
+                .iter()
+                .filter(|var| var.symbol.namespace == *namespace)
+                .map(|var| (get_varname(&var.symbol.qualified), var))
+                .collect();
+            if !state.is_empty() {
+                let defs: Vec<String> = state
+                    .iter()
+                    .map(|(varname, var)| {
+                        format!("{} = {}", varname, string_literal(&var.symbol.qualified))
+                    })
+                    .collect();
+                let properties: String = state
+                    .iter()
+                    .map(|(varname, var)| get_state_property(varname, var))
+                    .collect();
+                file.push_str(&format!(
+                    r###"
+    public static partial class State
+    {{
+        public static partial class Keys
+        {{
+            public const string {defs};
+        }}{properties}
+    }}
+"###,
+                    defs = defs.join(",\n                "),
+                    properties = properties,
+                ));
+            }
+            for command in &symbols.commands {
+                if command.symbol.namespace == *namespace {
+                    file.extend(get_params_struct(command, &get_command_varname(command))?);
+                }
+            }
+        }
+
+        Ok(SplitCodegen {
+            index: get_split_file(
+                "Each namespace is declared in its own file next to this one.",
+                &index,
+            ),
+            namespaces: namespaces
+                .iter()
+                .zip(files)
+                .map(|(namespace, file)| {
+                    (
+                        get_varname(namespace).trim_start_matches('@').to_string(),
+                        get_split_file(
+                            &format!(
+                                "It declares the `{}` namespace's {}.",
+                                namespace, SPLIT_MARKER
+                            ),
+                            &file,
+                        ),
+                    )
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Generates an abstract handler method for `command`, taking its params struct if it has parameters.
+fn get_handler_method(command: &CommandSymbol, attribute: &str, method: &str) -> String {
+    let params = if command.params.is_empty() {
+        String::new()
+    } else {
+        format!("{}Params @params", get_command_varname(command))
+    };
+    format!(
+        r###"{doc}        [{attribute}]
+        protected abstract void {method}({params});
+"###,
+        doc = get_doc(&command.symbol, "        "),
+        attribute = attribute,
+        method = method,
+        params = params,
+    )
+}
+
+/// Generates abstract `Handler` subclasses with a method per declared command and character command.
+pub(super) fn build_handlers(symbols: &Symbols) -> Result<String> {
+    let mut command_methods = Vec::new();
+    let mut character_methods = Vec::new();
+    for command in &symbols.commands {
+        if command.character {
+            let method = get_varname(&qualify(&command.symbol.namespace, &command.base_name));
+            let attribute = format!(
+                "CommandHandler({}, character: true)",
+                string_literal(&command.base_name)
+            );
+            character_methods.push((method, command, attribute));
+        } else {
+            let method = get_command_varname(command);
+            let attribute = format!(
+                "CommandHandler({})",
+                string_literal(&command.symbol.qualified)
+            );
+            command_methods.push((method, command, attribute));
+        }
+    }
+    for (kind, methods) in [
+        ("Command", &command_methods),
+        ("Character command", &character_methods),
+    ] {
+        check_varnames(
+            kind,
+            HANDLER_MEMBERS,
+            methods
+                .iter()
+                .map(|(method, command, _)| (method.as_str(), command.symbol.qualified.as_str())),
+        )?;
+    }
+
+    let characters: Vec<(String, &Symbol)> = symbols
+        .characters
+        .iter()
+        .map(|character| (get_varname(&character.qualified), character))
+        .collect();
+    check_varnames(
+        "Character",
+        &["Command", "Character"],
+        characters
+            .iter()
+            .map(|(varname, character)| (varname.as_str(), character.qualified.as_str())),
+    )?;
+    let character_classes: String = characters
+        .iter()
+        .map(|(varname, character)| {
+            format!(
+                r###"
+    /// <summary>
+    /// Autogenerated base class for handling the `{name}` character.
+    /// </summary>
+    public abstract class {varname}HandlerBase : CharacterHandlerBase
+    {{
+        public sealed override string Name => {literal};
+    }}
+"###,
+                name = character.qualified,
+                varname = varname,
+                literal = string_literal(&character.qualified),
+            )
+        })
+        .collect();
+
+    let get_methods = |methods: &[(String, &CommandSymbol, String)]| -> Vec<String> {
+        methods
+            .iter()
+            .map(|(method, command, attribute)| get_handler_method(command, attribute, method))
+            .collect()
+    };
+    Ok(format!(
+        r###"/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+
+namespace Kataru
+{{
+    /// <summary>
+    /// Autogenerated base class with a handler for each command.
+    /// Inherit from it and implement every method, so that missing handlers are compile errors.
+    /// </summary>
+    public abstract class CommandHandlerBase : Handler
+    {{
+{command_methods}    }}
+
+    /// <summary>
+    /// Autogenerated base class with a handler for a character's dialogue and each character command.
+    /// Inherit from a character's generated subclass, or from this class and override Name.
+    /// </summary>
+    public abstract class CharacterHandlerBase : Handler
+    {{
+        [CharacterHandler]
+        protected abstract void OnDialogue(Dialogue dialogue);
+{character_methods}    }}
+{character_classes}}}
+"###,
+        command_methods = get_methods(&command_methods).join("\n"),
+        character_methods = get_methods(&character_methods)
+            .iter()
+            .map(|method| format!("\n{}", method))
+            .collect::<String>(),
+        character_classes = character_classes,
+    ))
+}
@@ -0,0 +1,14 @@
+use super::{Backend, CodegenOptions, Symbols};
+use kataru::*;
+
+/// Generates a JSON manifest of every symbol, for build scripts and other tooling.
+pub struct JsonBackend;
+
+impl Backend for JsonBackend {
+    fn generate(&self, symbols: &Symbols, _options: &CodegenOptions) -> Result<String> {
+        match serde_json::to_string_pretty(symbols) {
+            Ok(json) => Ok(json + "\n"),
+            Err(err) => Err(error!("{}", err)),
+        }
+    }
+}
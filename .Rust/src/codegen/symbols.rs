@@ -0,0 +1,285 @@
+use super::{number_literal, string_literal};
+use crate::characters::CharacterConfigs;
+use crate::commands::{CHARACTER_COMMAND_PREFIX, RESULT_PARAM};
+use crate::ids::passage_line_ids;
+use crate::namespaces::qualify;
+use crate::sources::SourceFile;
+use kataru::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// An identifier declared in a namespace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Symbol {
+    pub namespace: String,
+    /// The name as declared, without its namespace.
+    pub name: String,
+    /// The name as used by the runner, e.g. `Room1:RedSlimeTalk`.
+    pub qualified: String,
+    /// Path of the file declaring the symbol, relative to the story directory.
+    /// Only known once `Symbols::locate_sources` has been called.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Lines describing the symbol for doc comments, e.g. a passage's first line of dialogue.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub doc: Vec<String>,
+}
+impl Symbol {
+    fn new(namespace: &str, name: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            qualified: qualify(namespace, name),
+            source: None,
+            doc: Vec::new(),
+        }
+    }
+
+    fn with_doc(mut self, doc: Vec<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+}
+
+/// Describes a passage by its first line of dialogue or narration.
+fn passage_doc(namespace: &str, name: &str, passage: &Passage) -> Vec<String> {
+    let line = passage_line_ids(namespace, name, passage)
+        .into_iter()
+        .find(|line| line.choice.is_none());
+    match line {
+        Some(line) => match line.speaker {
+            Some(speaker) => vec![format!("{}: {}", speaker, line.text)],
+            None => vec![line.text],
+        },
+        None => Vec::new(),
+    }
+}
+
+/// Describes a character by the description of its parsed config.
+/// `Symbols::describe_characters` replaces this with every field once the raw configs are known.
+fn character_doc(config: &Option<CharacterData>) -> Vec<String> {
+    match config {
+        Some(config) if !config.description.is_empty() => vec![config.description.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// Describes a character by the fields of its raw config, with its description first.
+fn character_config_doc(config: &serde_json::Value) -> Vec<String> {
+    let Some(fields) = config.as_object() else {
+        return Vec::new();
+    };
+    let mut doc = Vec::new();
+    if let Some(serde_json::Value::String(description)) = fields.get("description") {
+        if !description.is_empty() {
+            doc.push(description.clone());
+        }
+    }
+    let fields: Vec<String> = fields
+        .iter()
+        .filter(|(name, value)| name.as_str() != "description" && !value.is_null())
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(string) => string_literal(string),
+                value => value.to_string(),
+            };
+            format!("{} = {}", name, value)
+        })
+        .collect();
+    if !fields.is_empty() {
+        doc.push(format!("Fields: {}.", fields.join(", ")));
+    }
+    doc
+}
+
+/// Describes a command by its parameters and their defaults.
+fn command_doc(params: &[Param], result: &Option<String>) -> Vec<String> {
+    let mut doc = Vec::new();
+    if !params.is_empty() {
+        let params: Vec<String> = params
+            .iter()
+            .map(|param| format!("{} = {}", param.name, value_literal(&param.default)))
+            .collect();
+        doc.push(format!("Parameters: {}.", params.join(", ")));
+    }
+    if let Some(result) = result {
+        doc.push(format!("Result is assigned to {}.", result));
+    }
+    doc
+}
+
+/// Formats a value as it would be written in the story, quoting strings.
+fn value_literal(value: &Value) -> String {
+    match value {
+        Value::String(string) => string_literal(string),
+        Value::Number(number) => number_literal(*number, ".nan", ".inf"),
+        Value::Bool(boolean) => boolean.to_string(),
+    }
+}
+
+/// A declared command parameter with its default value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Param {
+    pub name: String,
+    pub default: Value,
+}
+
+/// A declared command.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandSymbol {
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    /// The command name without the `$character.` prefix of character commands.
+    pub base_name: String,
+    /// True for character commands, which are declared as `$character.Command` and called as `Character.Command`.
+    pub character: bool,
+    /// Parameters in declaration order, not including `$result`.
+    pub params: Vec<Param>,
+    /// Variable the command's result is assigned to, if it returns one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+}
+
+/// A declared state variable with its initial value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateSymbol {
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    pub value: Value,
+}
+
+/// Every identifier in a story that code may need to refer to, sorted by qualified name.
+/// Backends generate their source from this rather than walking the story themselves.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Symbols {
+    /// Namespaces other than the global namespace.
+    pub namespaces: Vec<String>,
+    pub passages: Vec<Symbol>,
+    pub characters: Vec<Symbol>,
+    pub commands: Vec<CommandSymbol>,
+    pub state: Vec<StateSymbol>,
+}
+
+impl Symbols {
+    pub fn extract(story: &Story) -> Self {
+        let mut symbols = Self::default();
+        for (namespace, section) in &story.sections {
+            if namespace != GLOBAL {
+                symbols.namespaces.push(namespace.clone());
+            }
+            for (name, passage) in &section.passages {
+                symbols.passages.push(
+                    Symbol::new(namespace, name).with_doc(passage_doc(namespace, name, passage)),
+                );
+            }
+            for (name, config) in &section.config.characters {
+                symbols
+                    .characters
+                    .push(Symbol::new(namespace, name).with_doc(character_doc(config)));
+            }
+            for (name, params) in &section.config.commands {
+                let base_name = name.strip_prefix(CHARACTER_COMMAND_PREFIX);
+                let result = match params.as_ref().and_then(|params| params.get(RESULT_PARAM)) {
+                    Some(Value::String(var)) => Some(var.clone()),
+                    _ => None,
+                };
+                let params: Vec<Param> = params
+                    .iter()
+                    .flatten()
+                    .filter(|(param, _)| param.as_str() != RESULT_PARAM)
+                    .map(|(param, default)| Param {
+                        name: param.clone(),
+                        default: default.clone(),
+                    })
+                    .collect();
+                symbols.commands.push(CommandSymbol {
+                    symbol: Symbol::new(namespace, name).with_doc(command_doc(&params, &result)),
+                    base_name: base_name.unwrap_or(name).to_string(),
+                    character: base_name.is_some(),
+                    params,
+                    result,
+                });
+            }
+            for (var, value) in &section.config.state {
+                // `$passage.` and `$character.` variables are templates for each passage and character.
+                if var.starts_with('$') {
+                    continue;
+                }
+                symbols.state.push(StateSymbol {
+                    symbol: Symbol::new(namespace, var)
+                        .with_doc(vec![format!("Initial value: {}.", value_literal(value))]),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        symbols.namespaces.sort();
+        symbols
+            .passages
+            .sort_by(|a, b| a.qualified.cmp(&b.qualified));
+        symbols
+            .characters
+            .sort_by(|a, b| a.qualified.cmp(&b.qualified));
+        symbols
+            .commands
+            .sort_by(|a, b| a.symbol.qualified.cmp(&b.symbol.qualified));
+        symbols
+            .state
+            .sort_by(|a, b| a.symbol.qualified.cmp(&b.symbol.qualified));
+        symbols
+    }
+
+    /// Describes each character by every field of its raw config in `configs`,
+    /// which keeps the fields kataru's parsed config drops.
+    pub fn describe_characters(&mut self, configs: &CharacterConfigs) {
+        for symbol in &mut self.characters {
+            let config = configs
+                .get(&symbol.namespace)
+                .and_then(|characters| characters.get(&symbol.name));
+            if let Some(config) = config {
+                symbol.doc = character_config_doc(config);
+            }
+        }
+    }
+
+    /// Fills in the `source` of each symbol with the file in `files` declaring it.
+    /// Symbols declared in several files are attributed to the first file in path order.
+    pub(crate) fn locate_sources(&mut self, files: &[SourceFile]) {
+        // Maps (kind, qualified name) to the declaring file.
+        let mut sources: HashMap<(&str, String), String> = HashMap::new();
+        for file in files {
+            let declared = [
+                (
+                    "passage",
+                    file.passages.iter().map(String::as_str).collect(),
+                ),
+                ("character", file.config_keys("characters")),
+                ("command", file.config_keys("commands")),
+                ("state", file.config_keys("state")),
+            ];
+            for (kind, names) in declared {
+                for name in names {
+                    sources
+                        .entry((kind, qualify(&file.namespace, name)))
+                        .or_insert_with(|| file.path.clone());
+                }
+            }
+        }
+
+        let locate = |kind: &'static str, symbol: &mut Symbol| {
+            symbol.source = sources.get(&(kind, symbol.qualified.clone())).cloned();
+        };
+        self.passages
+            .iter_mut()
+            .for_each(|symbol| locate("passage", symbol));
+        self.characters
+            .iter_mut()
+            .for_each(|symbol| locate("character", symbol));
+        self.commands
+            .iter_mut()
+            .for_each(|command| locate("command", &mut command.symbol));
+        self.state
+            .iter_mut()
+            .for_each(|var| locate("state", &mut var.symbol));
+    }
+}
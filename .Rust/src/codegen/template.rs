@@ -0,0 +1,239 @@
+use super::csharp::{
+    check_varnames, get_command_varname, get_summary, get_type_literal, get_varname, LIST_MEMBERS,
+};
+use super::{string_literal, Backend, CodegenOptions, SplitCodegen, Symbol, Symbols};
+use kataru::*;
+use minijinja::{AutoEscape, Environment};
+use serde::Serialize;
+use std::fs;
+
+/// The template reproducing the output of the C# backend with the flat layout.
+/// Copy it as a starting point for a custom template.
+pub const DEFAULT_TEMPLATE: &str = include_str!("Constants.cs.jinja");
+
+/// Renders a user-provided Jinja template over `namespaces`, `passages`, `characters`, `commands` and `variables`.
+/// See `Constants.cs.jinja` for the fields of each.
+pub struct TemplateBackend {
+    source: String,
+}
+
+impl TemplateBackend {
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+        }
+    }
+
+    /// Loads the template at `path`.
+    pub fn load(path: &str) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(source) => Ok(Self { source }),
+            Err(err) => Err(error!("Error reading codegen template '{}': {}", path, err)),
+        }
+    }
+}
+
+impl Default for TemplateBackend {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEMPLATE)
+    }
+}
+
+#[derive(Serialize)]
+struct NamespaceEntry<'a> {
+    name: &'a str,
+    varname: String,
+    literal: String,
+}
+
+#[derive(Serialize)]
+struct SymbolEntry<'a> {
+    #[serde(flatten)]
+    symbol: &'a Symbol,
+    varname: String,
+    literal: String,
+    summary: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ParamEntry<'a> {
+    name: &'a str,
+    varname: String,
+    #[serde(rename = "type")]
+    param_type: &'static str,
+    default: String,
+}
+
+#[derive(Serialize)]
+struct CommandEntry<'a> {
+    #[serde(flatten)]
+    symbol: &'a Symbol,
+    base_name: &'a str,
+    character: bool,
+    result: &'a Option<String>,
+    varname: String,
+    literal: String,
+    summary: Vec<String>,
+    params: Vec<ParamEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct VariableEntry<'a> {
+    #[serde(flatten)]
+    symbol: &'a Symbol,
+    varname: String,
+    literal: String,
+    summary: Vec<String>,
+    #[serde(rename = "type")]
+    var_type: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Context<'a> {
+    global: &'static str,
+    namespaces: Vec<NamespaceEntry<'a>>,
+    passages: Vec<SymbolEntry<'a>>,
+    characters: Vec<SymbolEntry<'a>>,
+    commands: Vec<CommandEntry<'a>>,
+    variables: Vec<VariableEntry<'a>>,
+}
+
+/// Gets the template entries for `symbols`, checking that no two generate the same C# identifier.
+fn get_symbol_entries<'a>(kind: &str, symbols: &'a [Symbol]) -> Result<Vec<SymbolEntry<'a>>> {
+    let entries: Vec<SymbolEntry> = symbols
+        .iter()
+        .map(|symbol| SymbolEntry {
+            symbol,
+            varname: get_varname(&symbol.qualified),
+            literal: string_literal(&symbol.qualified),
+            summary: get_summary(symbol),
+        })
+        .collect();
+    check_varnames(
+        kind,
+        LIST_MEMBERS,
+        entries
+            .iter()
+            .map(|entry| (entry.varname.as_str(), entry.symbol.qualified.as_str())),
+    )?;
+    Ok(entries)
+}
+
+impl Backend for TemplateBackend {
+    fn generate(&self, symbols: &Symbols, options: &CodegenOptions) -> Result<String> {
+        // Templates decide their own layout, so the C# backend's layout options can't apply.
+        if options.nested {
+            return Err(error!(
+                "Nested codegen output isn't supported with a codegen template. Disable nesting or clear the template."
+            ));
+        }
+        let namespaces: Vec<NamespaceEntry> = symbols
+            .namespaces
+            .iter()
+            .map(|name| NamespaceEntry {
+                name,
+                varname: get_varname(name),
+                literal: string_literal(name),
+            })
+            .collect();
+        check_varnames(
+            "Namespace",
+            &["Global", "All", "all"],
+            namespaces
+                .iter()
+                .map(|entry| (entry.varname.as_str(), entry.name)),
+        )?;
+
+        let mut commands = Vec::with_capacity(symbols.commands.len());
+        for command in &symbols.commands {
+            let params: Vec<ParamEntry> = command
+                .params
+                .iter()
+                .map(|param| {
+                    let (param_type, default) = get_type_literal(&param.default);
+                    ParamEntry {
+                        name: &param.name,
+                        varname: get_varname(&param.name),
+                        param_type,
+                        default,
+                    }
+                })
+                .collect();
+            check_varnames(
+                &format!("Parameter of command '{}'", command.symbol.qualified),
+                &["Default"],
+                params
+                    .iter()
+                    .map(|param| (param.varname.as_str(), param.name)),
+            )?;
+            commands.push(CommandEntry {
+                symbol: &command.symbol,
+                base_name: &command.base_name,
+                character: command.character,
+                result: &command.result,
+                varname: get_command_varname(command),
+                literal: string_literal(&command.symbol.qualified),
+                summary: get_summary(&command.symbol),
+                params,
+            });
+        }
+        check_varnames(
+            "Command",
+            LIST_MEMBERS,
+            commands
+                .iter()
+                .map(|entry| (entry.varname.as_str(), entry.symbol.qualified.as_str())),
+        )?;
+
+        let variables: Vec<VariableEntry> = symbols
+            .state
+            .iter()
+            .map(|var| {
+                let (var_type, value) = get_type_literal(&var.value);
+                VariableEntry {
+                    symbol: &var.symbol,
+                    varname: get_varname(&var.symbol.qualified),
+                    literal: string_literal(&var.symbol.qualified),
+                    summary: get_summary(&var.symbol),
+                    var_type,
+                    value,
+                }
+            })
+            .collect();
+        check_varnames(
+            "State variable",
+            &["None", "Keys"],
+            variables
+                .iter()
+                .map(|entry| (entry.varname.as_str(), entry.symbol.qualified.as_str())),
+        )?;
+
+        let context = Context {
+            global: GLOBAL,
+            namespaces,
+            passages: get_symbol_entries("Passage", &symbols.passages)?,
+            characters: get_symbol_entries("Character", &symbols.characters)?,
+            commands,
+            variables,
+        };
+
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.set_auto_escape_callback(|_| AutoEscape::None);
+        match env.render_str(&self.source, context) {
+            Ok(source) => Ok(source),
+            Err(err) => Err(error!("Error rendering codegen template: {}", err)),
+        }
+    }
+
+    fn generate_split(
+        &self,
+        _symbols: &Symbols,
+        _options: &CodegenOptions,
+    ) -> Result<SplitCodegen> {
+        Err(error!(
+            "Split codegen output isn't supported with a codegen template. Disable splitting or clear the template."
+        ))
+    }
+}
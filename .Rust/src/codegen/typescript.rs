@@ -0,0 +1,189 @@
+use super::{check_collisions, number_literal, sanitize, Backend, CodegenOptions};
+use super::{CommandSymbol, Symbol, Symbols};
+use crate::namespaces::qualify;
+use kataru::*;
+
+/// Generates TypeScript constant objects, name types and typed command parameters.
+pub struct TypeScriptBackend;
+
+/// Convert a kataru identifier to a TypeScript identifier, e.g. `Room1:RedSlime` to `Room1_RedSlime`.
+fn get_varname(name: &str) -> String {
+    sanitize(name, |c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Formats `text` as a TypeScript string literal.
+fn string_literal(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+/// Formats `name` as an object key, quoting it if it isn't a valid identifier.
+fn object_key(name: &str) -> String {
+    if get_varname(name) == name {
+        name.to_string()
+    } else {
+        string_literal(name)
+    }
+}
+
+/// Convert a declared command to a TypeScript varname, e.g. `Room1:$character.Wave` to `Room1_Character_Wave`.
+fn get_command_varname(command: &CommandSymbol) -> String {
+    let name = if command.character {
+        format!("Character_{}", command.base_name)
+    } else {
+        command.base_name.clone()
+    };
+    get_varname(&qualify(&command.symbol.namespace, &name))
+}
+
+/// Gets the TypeScript type and literal for a value.
+fn get_type_literal(value: &Value) -> (&'static str, String) {
+    match value {
+        Value::String(string) => ("string", string_literal(string)),
+        Value::Number(number) => ("number", number_literal(*number, "NaN", "Infinity")),
+        Value::Bool(boolean) => ("boolean", boolean.to_string()),
+    }
+}
+
+/// Generates a constant object mapping an identifier for each name to the name,
+/// and a type alias for the union of the names.
+fn get_const_object(kind: &str, object: &str, type_name: &str, names: &[&str]) -> Result<String> {
+    let entries: Vec<(String, &str)> = names
+        .iter()
+        .map(|name| (get_varname(name), *name))
+        .collect();
+    check_collisions(
+        "TypeScript",
+        kind,
+        &[],
+        entries
+            .iter()
+            .map(|(varname, name)| (varname.as_str(), *name)),
+    )?;
+    let members: String = entries
+        .iter()
+        .map(|(varname, name)| format!("  {}: {},\n", varname, string_literal(name)))
+        .collect();
+    Ok(format!(
+        r###"
+export const {object} = {{
+{members}}} as const;
+export type {type_name} = (typeof {object})[keyof typeof {object}];
+"###,
+        object = object,
+        members = members,
+        type_name = type_name,
+    ))
+}
+
+/// Generates an interface for the parameters of a command and a constant of their defaults.
+fn get_params_interface(command: &CommandSymbol, varname: &str) -> String {
+    if command.params.is_empty() {
+        return String::new();
+    }
+    let mut fields = String::new();
+    let mut defaults = String::new();
+    for param in &command.params {
+        let key = object_key(&param.name);
+        let (param_type, literal) = get_type_literal(&param.default);
+        fields.push_str(&format!("  {}: {};\n", key, param_type));
+        defaults.push_str(&format!("  {}: {},\n", key, literal));
+    }
+    format!(
+        r###"
+/** Parameters of the `{command}` command. */
+export interface {varname}Params {{
+{fields}}}
+export const {varname}Defaults: {varname}Params = {{
+{defaults}}};
+"###,
+        command = command.symbol.qualified,
+        varname = varname,
+        fields = fields,
+        defaults = defaults,
+    )
+}
+
+fn qualified(symbols: &[Symbol]) -> Vec<&str> {
+    symbols
+        .iter()
+        .map(|symbol| symbol.qualified.as_str())
+        .collect()
+}
+
+impl Backend for TypeScriptBackend {
+    fn generate(&self, symbols: &Symbols, _options: &CodegenOptions) -> Result<String> {
+        let namespaces: Vec<&str> = symbols.namespaces.iter().map(String::as_str).collect();
+        let state: Vec<&str> = symbols
+            .state
+            .iter()
+            .map(|var| var.symbol.qualified.as_str())
+            .collect();
+
+        let commands: Vec<(String, &CommandSymbol)> = symbols
+            .commands
+            .iter()
+            .map(|command| (get_command_varname(command), command))
+            .collect();
+        check_collisions(
+            "TypeScript",
+            "Command",
+            &[],
+            commands
+                .iter()
+                .map(|(varname, command)| (varname.as_str(), command.symbol.qualified.as_str())),
+        )?;
+        let mut command_members = String::new();
+        let mut command_params = String::new();
+        for (varname, command) in &commands {
+            command_members.push_str(&format!(
+                "  {}: {},\n",
+                varname,
+                string_literal(&command.symbol.qualified)
+            ));
+            command_params.push_str(&get_params_interface(command, varname));
+        }
+
+        let state_types: String = symbols
+            .state
+            .iter()
+            .map(|var| {
+                format!(
+                    "  {}: {};\n",
+                    object_key(&var.symbol.qualified),
+                    get_type_literal(&var.value).0
+                )
+            })
+            .collect();
+
+        Ok(format!(
+            r###"// DO NOT EDIT.
+// This file was autogenerated by Kataru based on your scripts.
+{namespaces}{passages}{characters}
+export const Commands = {{
+{command_members}}} as const;
+export type Command = (typeof Commands)[keyof typeof Commands];
+{state}
+/** The type of each state variable. */
+export interface StateTypes {{
+{state_types}}}
+{command_params}"###,
+            namespaces = get_const_object("Namespace", "Namespaces", "Namespace", &namespaces)?,
+            passages = get_const_object(
+                "Passage",
+                "Passages",
+                "Passage",
+                &qualified(&symbols.passages)
+            )?,
+            characters = get_const_object(
+                "Character",
+                "Characters",
+                "Character",
+                &qualified(&symbols.characters)
+            )?,
+            command_members = command_members,
+            state = get_const_object("State variable", "State", "StateVariable", &state)?,
+            state_types = state_types,
+            command_params = command_params,
+        ))
+    }
+}
@@ -1,7 +1,82 @@
-pub use crate::{ffi::FFIStr, LINE};
+use crate::ffi::json_or_error;
+use crate::lines::{flatten, FlatLine};
+use crate::namespaces::{qualify, resolution_order, resolve, split_qualified};
+pub use crate::{ffi::FFIStr, LINE, RUNNER};
 use kataru::*;
+use serde::Serialize;
+use std::os::raw::c_char;
 
 static mut PARAMS_JSON: String = String::new();
+static mut COMMANDS_JSON: String = String::new();
+static mut HANDLER_REPORT_JSON: String = String::new();
+
+/// Prefix of commands declared for every character, e.g. `$character.SetAnimatorTrigger`.
+pub const CHARACTER_COMMAND_PREFIX: &str = "$character.";
+
+/// Reserved parameter naming the state variable a command's result is assigned to.
+pub const RESULT_PARAM: &str = "$result";
+
+/// A declared command parameter.
+#[derive(Debug, Serialize)]
+struct ParamInfo<'a> {
+    name: &'a str,
+    default: &'a Value,
+    #[serde(rename = "type")]
+    param_type: &'static str,
+}
+
+/// A declared command along with the namespace it was resolved in.
+#[derive(Debug, Serialize)]
+struct CommandInfo<'a> {
+    name: String,
+    namespace: &'a str,
+    base_name: &'a str,
+    character: bool,
+    params: Vec<ParamInfo<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a Value>,
+}
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "bool",
+    }
+}
+
+/// The parts of a command name from the runner, e.g. `Room1:RedSlime.Walk` or `Room1:Open`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommandName<'a> {
+    /// The qualified character for character commands, otherwise empty.
+    pub character: &'a str,
+    /// The command name without its namespace or character.
+    pub base_name: &'a str,
+    /// The full name used to dispatch the command.
+    pub qualified_name: &'a str,
+}
+impl<'a> CommandName<'a> {
+    /// Splits a command name built by the runner.
+    pub fn parse(name: &'a str) -> Self {
+        let (character, base_name) = match name.rsplit_once('.') {
+            Some((character, base_name)) => (character, base_name),
+            None => ("", name.rsplit_once(':').map_or(name, |(_, base)| base)),
+        };
+        Self {
+            character,
+            base_name,
+            qualified_name: name,
+        }
+    }
+}
+
+/// Gets the parts of the current command's name, or None if the line isn't a command.
+unsafe fn current_command_name() -> Option<CommandName<'static>> {
+    match &LINE {
+        Line::Command(command) => Some(CommandName::parse(&command.name)),
+        _ => None,
+    }
+}
 
 #[no_mangle]
 pub extern "C" fn get_command() -> FFIStr {
@@ -13,6 +88,110 @@ pub extern "C" fn get_command() -> FFIStr {
     }
 }
 
+/// Gets the qualified character of the current character command, or an empty string.
+#[no_mangle]
+pub extern "C" fn get_command_character() -> FFIStr {
+    unsafe { FFIStr::from(current_command_name().map_or("", |name| name.character)) }
+}
+
+/// Gets the name of the current command without its namespace or character.
+#[no_mangle]
+pub extern "C" fn get_command_base_name() -> FFIStr {
+    unsafe { FFIStr::from(current_command_name().map_or("", |name| name.base_name)) }
+}
+
+/// Gets the fully qualified name of the current command, which handlers are dispatched by.
+#[no_mangle]
+pub extern "C" fn get_command_qualified_name() -> FFIStr {
+    unsafe { FFIStr::from(current_command_name().map_or("", |name| name.qualified_name)) }
+}
+
+/// Type of a command parameter, as returned by `get_param_type`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamType {
+    None,
+    String,
+    Number,
+    Bool,
+}
+impl From<&Value> for ParamType {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(_) => Self::String,
+            Value::Number(_) => Self::Number,
+            Value::Bool(_) => Self::Bool,
+        }
+    }
+}
+
+/// Gets the parameters of the current command, or None if the line isn't a command.
+unsafe fn current_params() -> Option<&'static Params> {
+    match &LINE {
+        Line::Command(command) => Some(&command.params),
+        _ => None,
+    }
+}
+
+/// Gets the i-th parameter of the current command.
+unsafe fn current_param_at(i: usize) -> Option<(&'static String, &'static Value)> {
+    current_params()?.iter().nth(i)
+}
+
+/// Gets the parameter of the current command named `name`.
+unsafe fn current_param(name: &str) -> Option<&'static Value> {
+    current_params()?.get(name)
+}
+
+/// Gets the number of parameters of the current command, or 0 if the line isn't a command.
+#[no_mangle]
+pub extern "C" fn get_param_count() -> usize {
+    unsafe { current_params().map_or(0, |params| params.len()) }
+}
+
+/// Gets the name of the i-th parameter of the current command, or an empty string.
+#[no_mangle]
+pub extern "C" fn get_param_name(i: usize) -> FFIStr {
+    unsafe { FFIStr::from(current_param_at(i).map_or("", |(name, _)| name)) }
+}
+
+/// Gets the type of the i-th parameter of the current command.
+#[no_mangle]
+pub extern "C" fn get_param_type(i: usize) -> ParamType {
+    unsafe { current_param_at(i).map_or(ParamType::None, |(_, value)| value.into()) }
+}
+
+/// Gets a number parameter of the current command, or 0 if it isn't a number.
+#[no_mangle]
+pub extern "C" fn get_param_number(name: *const c_char, length: usize) -> f64 {
+    let name = FFIStr::to_str(name, length);
+    unsafe {
+        match current_param(name) {
+            Some(Value::Number(number)) => *number,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Gets a bool parameter of the current command, or false if it isn't a bool.
+#[no_mangle]
+pub extern "C" fn get_param_bool(name: *const c_char, length: usize) -> bool {
+    let name = FFIStr::to_str(name, length);
+    unsafe { matches!(current_param(name), Some(Value::Bool(true))) }
+}
+
+/// Gets a string parameter of the current command, or an empty string if it isn't a string.
+#[no_mangle]
+pub extern "C" fn get_param_string(name: *const c_char, length: usize) -> FFIStr {
+    let name = FFIStr::to_str(name, length);
+    unsafe {
+        match current_param(name) {
+            Some(Value::String(string)) => FFIStr::from(string),
+            _ => FFIStr::from(""),
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn get_params() -> FFIStr {
     unsafe {
@@ -27,3 +206,338 @@ pub extern "C" fn get_params() -> FFIStr {
         }
     }
 }
+
+/// Lists every command visible from `namespace` as JSON, innermost namespace first.
+pub fn build_command_list(story: &Story, namespace: &str) -> Result<String> {
+    let mut seen = Vec::<&str>::new();
+    let mut commands = Vec::<CommandInfo>::new();
+    for resolved_namespace in resolution_order(namespace) {
+        let Some(section) = story.sections.get(resolved_namespace) else {
+            return Err(error!("Namespace '{}' does not exist", resolved_namespace));
+        };
+        let mut names: Vec<&String> = section.config.commands.keys().collect();
+        names.sort();
+        for name in names {
+            if seen.contains(&name.as_str()) {
+                continue;
+            }
+            seen.push(name);
+            let config = section.config.commands[name].as_ref();
+            let params = match config {
+                Some(params) => params
+                    .iter()
+                    .filter(|(param, _)| param.as_str() != RESULT_PARAM)
+                    .map(|(param, default)| ParamInfo {
+                        name: param,
+                        default,
+                        param_type: value_type(default),
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+            let base_name = name.strip_prefix(CHARACTER_COMMAND_PREFIX);
+            commands.push(CommandInfo {
+                name: qualify(resolved_namespace, name),
+                namespace: resolved_namespace,
+                base_name: base_name.unwrap_or(name),
+                character: base_name.is_some(),
+                params,
+                result: config.and_then(|params| params.get(RESULT_PARAM)),
+            });
+        }
+    }
+    serde_json::to_string(&commands).map_err(|err| error!("{}", err))
+}
+
+fn try_list_commands(namespace: &str) -> Result<String> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_ref() {
+            build_command_list(runner.story(), namespace)
+        } else {
+            Err(error!("Runner was not initialized."))
+        }
+    }
+}
+#[no_mangle]
+pub extern "C" fn list_commands(namespace: *const c_char, length: usize) -> FFIStr {
+    let namespace = FFIStr::to_str(namespace, length);
+    unsafe {
+        COMMANDS_JSON = json_or_error(try_list_commands(namespace));
+        FFIStr::from(&COMMANDS_JSON)
+    }
+}
+
+/// Mismatches between the story's declarations and the handlers registered by the host.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct HandlerReport {
+    /// Declared commands that no handler is registered for.
+    pub unhandled_commands: Vec<String>,
+    /// Registered command handlers that don't match any declared command.
+    pub undeclared_handlers: Vec<String>,
+    /// Declared characters that no character handler is registered for.
+    pub unhandled_characters: Vec<String>,
+}
+
+/// Compares the commands and characters declared in `story` against registered handler names.
+pub fn build_handler_report(
+    story: &Story,
+    command_handlers: &[&str],
+    character_handlers: &[&str],
+) -> HandlerReport {
+    let mut report = HandlerReport::default();
+    let handler_names: Vec<CommandName> = command_handlers
+        .iter()
+        .map(|handler| CommandName::parse(handler))
+        .collect();
+    for (namespace, section) in &story.sections {
+        for name in section.config.commands.keys() {
+            let handled = match name.strip_prefix(CHARACTER_COMMAND_PREFIX) {
+                Some(base_name) => handler_names
+                    .iter()
+                    .any(|handler| !handler.character.is_empty() && handler.base_name == base_name),
+                None => {
+                    let qualified_name = qualify(namespace, name);
+                    handler_names
+                        .iter()
+                        .any(|handler| handler.qualified_name == qualified_name)
+                }
+            };
+            if !handled {
+                report.unhandled_commands.push(qualify(namespace, name));
+            }
+        }
+        for name in section.config.characters.keys() {
+            let qualified_name = qualify(namespace, name);
+            if !character_handlers.contains(&qualified_name.as_str()) {
+                report.unhandled_characters.push(qualified_name);
+            }
+        }
+    }
+    for handler in &handler_names {
+        let declared = if handler.character.is_empty() {
+            let (namespace, name) = split_qualified(GLOBAL, handler.qualified_name);
+            story
+                .sections
+                .get(namespace)
+                .is_some_and(|section| section.config.commands.contains_key(name))
+        } else {
+            // Character commands are resolved from the character's namespace.
+            let (namespace, _) = split_qualified(GLOBAL, handler.character);
+            let name = format!("{}{}", CHARACTER_COMMAND_PREFIX, handler.base_name);
+            resolution_order(namespace).into_iter().any(|namespace| {
+                story
+                    .sections
+                    .get(namespace)
+                    .is_some_and(|section| section.config.commands.contains_key(&name))
+            })
+        };
+        if !declared {
+            report
+                .undeclared_handlers
+                .push(handler.qualified_name.to_string());
+        }
+    }
+    report.unhandled_commands.sort();
+    report.undeclared_handlers.sort();
+    report.unhandled_characters.sort();
+    report
+}
+
+fn try_check_handlers(command_handlers: &str, character_handlers: &str) -> Result<String> {
+    let parse = |json: &str| -> Result<Vec<String>> {
+        serde_json::from_str(json).map_err(|err| error!("Invalid handler list: {}", err))
+    };
+    let command_handlers = parse(command_handlers)?;
+    let character_handlers = parse(character_handlers)?;
+    unsafe {
+        let Some(runner) = RUNNER.as_ref() else {
+            return Err(error!("Runner was not initialized."));
+        };
+        let report = build_handler_report(
+            runner.story(),
+            &command_handlers
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<&str>>(),
+            &character_handlers
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<&str>>(),
+        );
+        serde_json::to_string(&report).map_err(|err| error!("{}", err))
+    }
+}
+/// Takes JSON lists of registered command and character handler names,
+/// and returns a JSON report of declarations and handlers that don't match up.
+#[no_mangle]
+pub extern "C" fn check_handlers(
+    command_handlers: *const c_char,
+    command_handlers_length: usize,
+    character_handlers: *const c_char,
+    character_handlers_length: usize,
+) -> FFIStr {
+    let command_handlers = FFIStr::to_str(command_handlers, command_handlers_length);
+    let character_handlers = FFIStr::to_str(character_handlers, character_handlers_length);
+    unsafe {
+        HANDLER_REPORT_JSON =
+            json_or_error(try_check_handlers(command_handlers, character_handlers));
+        FFIStr::from(&HANDLER_REPORT_JSON)
+    }
+}
+
+/// Resolves the `$result` variable of the command `name` declared in `namespace`.
+/// Returns the variable qualified by the namespace it was found in, and its declared value.
+fn resolve_result<'s>(
+    story: &'s Story,
+    namespace: &str,
+    name: &str,
+    result: &Value,
+) -> Result<(String, &'s Value)> {
+    let resolved = match result {
+        Value::String(var) => resolve(story, namespace, var, |section, var| section.value(var))
+            .ok()
+            .map(|(var_namespace, value)| {
+                let var = split_qualified(var_namespace, var).1;
+                (format!("{}:{}", var_namespace, var), value)
+            }),
+        _ => None,
+    };
+    resolved.ok_or_else(|| {
+        error!(
+            "Command '{}': result variable '{}' is not declared.",
+            qualify(namespace, name),
+            result
+        )
+    })
+}
+
+/// Checks that each command's `$result` names a declared state variable,
+/// and that no call passes a value for it, since kataru fills it like any other parameter.
+pub fn validate_command_results(story: &Story) -> Result<()> {
+    for (namespace, section) in &story.sections {
+        for (name, params) in &section.config.commands {
+            if let Some(result) = params.as_ref().and_then(|params| params.get(RESULT_PARAM)) {
+                resolve_result(story, namespace, name, result)?;
+            }
+        }
+    }
+
+    let mut namespaces: Vec<&String> = story.sections.keys().collect();
+    namespaces.sort();
+    for namespace in namespaces {
+        let passages = &story.sections[namespace].passages;
+        let mut passage_names: Vec<&String> = passages.keys().collect();
+        passage_names.sort();
+        for passage_name in passage_names {
+            for line in flatten(&passages[passage_name]) {
+                let error = match line {
+                    FlatLine::Raw(RawLine::Command(command)) => command
+                        .iter()
+                        .find(|(_, params)| params.contains_key(RESULT_PARAM))
+                        .map(|(name, _)| format!("command '{}' passes a value for '{}'", name, RESULT_PARAM)),
+                    FlatLine::Raw(RawLine::PositionalCommand(command)) => command
+                        .iter()
+                        .find(|(name, args)| {
+                            declared_params(story, namespace, name)
+                                .and_then(|params| params.keys().position(|param| param == RESULT_PARAM))
+                                .is_some_and(|position| args.len() > position)
+                        })
+                        .map(|(name, args)| {
+                            format!(
+                                "command '{}' is called with {} arguments, which overwrites its '{}' parameter",
+                                name,
+                                args.len(),
+                                RESULT_PARAM
+                            )
+                        }),
+                    _ => None,
+                };
+                if let Some(error) = error {
+                    return Err(error!(
+                        "Passage '{}': {}.",
+                        qualify(namespace, passage_name),
+                        error
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gets the declared parameters of a command called as `name` from `namespace`, e.g. `Alice.Wave`.
+fn declared_params<'s>(story: &'s Story, namespace: &str, name: &str) -> Option<&'s Params> {
+    let name = match name.split_once('.') {
+        Some((_character, name)) => format!("{}{}", CHARACTER_COMMAND_PREFIX, name),
+        None => name.to_string(),
+    };
+    resolve(story, namespace, &name, |section, name| {
+        section.params(name)
+    })
+    .ok()?
+    .1
+    .as_ref()
+}
+
+/// Assigns `result` to the variable named by the command's `$result` parameter.
+pub fn complete(runner: &mut Runner, command: &Command, result: Value) -> Result<()> {
+    // Character commands are declared as `$character.Command` and resolved from the current namespace,
+    // while other command names are already qualified by the namespace declaring them.
+    let name = CommandName::parse(&command.name);
+    let declared_name = if name.character.is_empty() {
+        command.name.clone()
+    } else {
+        format!("{}{}", CHARACTER_COMMAND_PREFIX, name.base_name)
+    };
+    let story = runner.story();
+    let (namespace, params) = resolve(
+        story,
+        runner.namespace(),
+        &declared_name,
+        |section, name| section.params(name),
+    )?;
+    let Some(result_var) = params.as_ref().and_then(|params| params.get(RESULT_PARAM)) else {
+        return Err(error!(
+            "Command '{}' does not declare a '{}' variable.",
+            command.name, RESULT_PARAM
+        ));
+    };
+    let unqualified_name = split_qualified(namespace, &declared_name).1;
+    let (var, declared) = resolve_result(story, namespace, unqualified_name, result_var)?;
+    if value_type(&result) != value_type(declared) {
+        return Err(error!(
+            "Command '{}': result {} is a {}, but '{}' is a {}.",
+            command.name,
+            result,
+            value_type(&result),
+            var,
+            value_type(declared)
+        ));
+    }
+    runner.set_state(
+        StateMod {
+            var: &var,
+            op: AssignOperator::None,
+        },
+        result,
+    )
+}
+
+fn try_complete_command(result: &str) -> Result<()> {
+    let result = Value::from_yml(result)?;
+    unsafe {
+        let Some(runner) = RUNNER.as_mut() else {
+            return Err(error!("Runner was not initialized."));
+        };
+        let Line::Command(command) = &LINE else {
+            return Err(error!("Called complete_command on a non-command line."));
+        };
+        complete(runner, command, result)
+    }
+}
+/// Completes the current command with a JSON `result`, e.g. `4`, `true` or `"sword"`.
+#[no_mangle]
+pub extern "C" fn complete_command(result: *const c_char, length: usize) -> FFIStr {
+    let result = FFIStr::to_str(result, length);
+    FFIStr::result(try_complete_command(result))
+}
@@ -1,7 +1,147 @@
-pub use crate::{ffi::FFIStr, LINE};
+use crate::ids::{current_source_text, line_ids, split_line_id, LineId};
+use crate::interpolation::Interpolated;
+use crate::locale::translate;
+use crate::richtext::{RichSpan, RichText};
+use crate::timeline::{Markers, TimelineEvent};
+pub use crate::{ffi::FFIStr, LINE, RUNNER};
 use kataru::*;
+use std::os::raw::c_char;
 
 static mut ATTRIBUTES_JSON: String = String::new();
+static mut RICH_TEXT_JSON: String = String::new();
+static mut TIMELINE_JSON: String = String::new();
+static mut SPEECH: Speech = Speech::new();
+static mut SPEECH_OPTIONS: SpeechOptions = SpeechOptions::new();
+
+/// Host-configurable processing applied to dialogue text.
+#[derive(Debug, Clone, Default)]
+pub struct SpeechOptions {
+    /// If true, rich text tags are stripped from the text into spans.
+    pub rich_text: bool,
+    /// Tags left in the text for the engine to render natively.
+    pub passthrough: Vec<String>,
+}
+impl SpeechOptions {
+    pub const fn new() -> Self {
+        Self {
+            rich_text: false,
+            passthrough: Vec::new(),
+        }
+    }
+}
+
+/// Text of a dialogue line after processing, along with its annotations.
+/// All offsets are character offsets into `text`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Speech {
+    pub text: String,
+    pub attributes: Vec<AttributedSpan>,
+    pub spans: Vec<RichSpan>,
+    pub timeline: Vec<TimelineEvent>,
+}
+impl Speech {
+    pub const fn new() -> Self {
+        Self {
+            text: String::new(),
+            attributes: Vec::new(),
+            spans: Vec::new(),
+            timeline: Vec::new(),
+        }
+    }
+
+    /// Processes `dialogue` according to `options`.
+    /// If `runner` is given, the dialogue is rebuilt from the source text of
+    /// the runner's current line, or its translation in the current locale looked up in `line_ids`,
+    /// so that `${var}` references can be formatted and inline markers can be extracted.
+    pub fn build(
+        runner: Option<&Runner>,
+        line_ids: &[LineId],
+        dialogue: &Dialogue,
+        options: &SpeechOptions,
+    ) -> Result<Self> {
+        let mut markers = Markers::default();
+        let mut interpolated = Interpolated::default();
+        let mut speech = Self {
+            text: split_line_id(&dialogue.text).0.to_string(),
+            attributes: dialogue.attributes.clone(),
+            ..Self::default()
+        };
+        if let Some(runner) = runner {
+            let source = translate(line_ids, None).or_else(|| current_source_text(runner));
+            if let Some(source) = source {
+                let bookmark = runner.bookmark();
+                interpolated = Interpolated::mark(source, |var| bookmark.value(var).ok());
+                markers = Markers::mark(&interpolated.text);
+                let marked_dialogue =
+                    Dialogue::from(&dialogue.name, &markers.text, runner.story(), bookmark)?;
+                speech.text = marked_dialogue.text;
+                speech.attributes = marked_dialogue.attributes;
+            }
+        }
+
+        if options.rich_text {
+            let rich_text = RichText::parse(&speech.text, &options.passthrough);
+            for span in &mut speech.attributes {
+                span.start = rich_text.map_byte(span.start);
+                span.end = rich_text.map_byte(span.end);
+            }
+            speech.text = rich_text.text;
+            speech.spans = rich_text.spans;
+        }
+
+        let timeline = markers.resolve(&speech.text);
+        for span in &mut speech.attributes {
+            span.start = timeline.map_byte(span.start);
+            span.end = timeline.map_byte(span.end);
+        }
+        for span in &mut speech.spans {
+            span.start = timeline.map_char(span.start);
+            span.end = timeline.map_char(span.end);
+        }
+        speech.text = timeline.text;
+        speech.timeline = timeline.events;
+
+        // Kataru's attribute spans are byte offsets, but spans and timeline events count characters.
+        for span in &mut speech.attributes {
+            span.start = char_offset(&speech.text, span.start);
+            span.end = char_offset(&speech.text, span.end);
+        }
+
+        // Values are only swapped back in once all markup has been parsed.
+        let (text, offsets) = interpolated.resolve(&speech.text);
+        for span in &mut speech.attributes {
+            span.start = offsets[span.start];
+            span.end = offsets[span.end];
+        }
+        for span in &mut speech.spans {
+            span.start = offsets[span.start];
+            span.end = offsets[span.end];
+        }
+        for event in &mut speech.timeline {
+            event.index = offsets[event.index];
+        }
+        speech.text = text;
+        Ok(speech)
+    }
+}
+
+/// Converts a byte offset in `text` to a character offset.
+fn char_offset(text: &str, byte: usize) -> usize {
+    text.char_indices().take_while(|(i, _)| *i < byte).count()
+}
+
+/// Rebuilds the speech for the current line, which must be called whenever `LINE`,
+/// the speech options or the locale change.
+pub fn load_speech() -> Result<()> {
+    unsafe {
+        SPEECH = if let Line::Dialogue(dialogue) = &LINE {
+            Speech::build(RUNNER.as_ref(), line_ids(), dialogue, &SPEECH_OPTIONS)?
+        } else {
+            Speech::new()
+        };
+    }
+    Ok(())
+}
 
 #[no_mangle]
 pub extern "C" fn get_speaker() -> FFIStr {
@@ -16,26 +156,72 @@ pub extern "C" fn get_speaker() -> FFIStr {
 
 #[no_mangle]
 pub extern "C" fn get_speech() -> FFIStr {
+    unsafe { FFIStr::from(&SPEECH.text) }
+}
+
+#[no_mangle]
+pub extern "C" fn get_attributes() -> FFIStr {
     unsafe {
-        if let Line::Dialogue(dialogue) = &LINE {
-            FFIStr::from(&dialogue.text)
+        if let Line::Dialogue(_) = &LINE {
+            ATTRIBUTES_JSON = match serde_json::to_string(&SPEECH.attributes) {
+                Ok(json) => json,
+                Err(err) => format!("{{\"error\": \"{}\"}}", err),
+            };
+            FFIStr::from(&ATTRIBUTES_JSON)
         } else {
-            FFIStr::from("")
+            FFIStr::from("{\"error\": \"Called get_params on a non-dialogue line.\"}")
         }
     }
 }
 
 #[no_mangle]
-pub extern "C" fn get_attributes() -> FFIStr {
+pub extern "C" fn get_rich_text_spans() -> FFIStr {
     unsafe {
-        if let Line::Dialogue(dialogue) = &LINE {
-            ATTRIBUTES_JSON = match serde_json::to_string(&dialogue.attributes) {
+        if let Line::Dialogue(_) = &LINE {
+            RICH_TEXT_JSON = match serde_json::to_string(&SPEECH.spans) {
                 Ok(json) => json,
                 Err(err) => format!("{{\"error\": \"{}\"}}", err),
             };
-            FFIStr::from(&ATTRIBUTES_JSON)
+            FFIStr::from(&RICH_TEXT_JSON)
         } else {
-            FFIStr::from("{\"error\": \"Called get_params on a non-dialogue line.\"}")
+            FFIStr::from("{\"error\": \"Called get_rich_text_spans on a non-dialogue line.\"}")
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn get_timeline() -> FFIStr {
+    unsafe {
+        if let Line::Dialogue(_) = &LINE {
+            TIMELINE_JSON = match serde_json::to_string(&SPEECH.timeline) {
+                Ok(json) => json,
+                Err(err) => format!("{{\"error\": \"{}\"}}", err),
+            };
+            FFIStr::from(&TIMELINE_JSON)
+        } else {
+            FFIStr::from("{\"error\": \"Called get_timeline on a non-dialogue line.\"}")
         }
     }
 }
+
+/// Enables or disables stripping rich text tags from speech into spans.
+#[no_mangle]
+pub extern "C" fn set_rich_text(enabled: bool) -> FFIStr {
+    unsafe { SPEECH_OPTIONS.rich_text = enabled }
+    FFIStr::result(load_speech())
+}
+
+/// Sets the comma separated list of tags to leave in the text when rich text is enabled.
+#[no_mangle]
+pub extern "C" fn set_rich_text_passthrough(tags: *const c_char, length: usize) -> FFIStr {
+    let tags = FFIStr::to_str(tags, length);
+    unsafe {
+        SPEECH_OPTIONS.passthrough = tags
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    FFIStr::result(load_speech())
+}
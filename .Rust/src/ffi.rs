@@ -51,3 +51,11 @@ impl FFIStr {
         }
     }
 }
+
+/// Returns the JSON in `result`, or a JSON object describing the error.
+pub fn json_or_error<E: fmt::Display>(result: Result<String, E>) -> String {
+    match result {
+        Ok(json) => json,
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    }
+}
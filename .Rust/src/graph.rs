@@ -0,0 +1,413 @@
+use crate::lines::{flatten, FlatLine};
+use crate::namespaces::{qualify, resolution_order, resolve, split_qualified};
+pub use crate::{ffi::FFIStr, RUNNER};
+use kataru::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::{fs, os::raw::c_char, path::Path};
+
+/// A passage in the story graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphNode {
+    /// The passage's qualified name, e.g. `Room1:RedSlimeTalk`.
+    pub id: String,
+    pub namespace: String,
+    pub passage: String,
+    /// True if the passage is referenced but doesn't exist.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+}
+
+/// How control flows along an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    Choice,
+    /// A choices' `default`, called when the timeout runs out.
+    Timeout,
+    Call,
+    /// A `call` right before a return, which the runner turns into a jump.
+    Goto,
+}
+
+/// An edge from one passage to another.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Conditions of the enclosing branches and conditional choices, outermost first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<String>,
+}
+
+/// The passages of a story and the edges between them, global namespace first.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct StoryGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Walks a passage's lines, collecting edges.
+struct GraphBuilder<'s> {
+    story: &'s Story,
+    namespace: &'s str,
+    from: String,
+    flat: Vec<FlatLine<'s>>,
+    /// False if there's an `onExit` to run, which keeps calls before a return from being jumps.
+    tail_calls: bool,
+    edges: Vec<GraphEdge>,
+    missing: Vec<NodeKey>,
+}
+impl<'s> GraphBuilder<'s> {
+    /// Resolves `passage` the same way the runner does, recording it as missing if it can't be.
+    fn resolve(&mut self, passage: &str) -> String {
+        match resolve(self.story, self.namespace, passage, Section::passage) {
+            Ok((namespace, _)) => qualify(namespace, split_qualified(namespace, passage).1),
+            Err(_) => {
+                let (namespace, name) = split_qualified(self.namespace, passage);
+                self.missing.push(node_key(namespace, name));
+                qualify(namespace, name)
+            }
+        }
+    }
+
+    fn push(&mut self, passage: &str, kind: EdgeKind, label: Option<&str>, conditions: &[String]) {
+        let to = self.resolve(passage);
+        self.edges.push(GraphEdge {
+            from: self.from.clone(),
+            to,
+            kind,
+            label: label.map(str::to_string),
+            conditions: conditions.to_vec(),
+        });
+    }
+
+    /// Gets the kind of edge for `call`, which is a jump if the runner returns right after it.
+    fn call_kind(&self, call: &RawLine) -> EdgeKind {
+        let line = self.flat.iter().position(|line| match line {
+            FlatLine::Raw(raw) => std::ptr::eq(*raw, call),
+            _ => false,
+        });
+        let returns = matches!(
+            line.and_then(|line| self.flat.get(line + 1)),
+            Some(FlatLine::Return | FlatLine::Raw(RawLine::Return(_)))
+        );
+        if returns && self.tail_calls {
+            EdgeKind::Goto
+        } else {
+            EdgeKind::Call
+        }
+    }
+
+    /// Walks `lines` under `conditions`.
+    fn walk(&mut self, lines: &'s [RawLine], conditions: &[String]) {
+        for line in lines {
+            match line {
+                RawLine::Call(call) => {
+                    let kind = self.call_kind(line);
+                    self.push(&call.passage, kind, None, conditions);
+                }
+                RawLine::Branches(branches) => {
+                    // A branch is only taken if the ones before it weren't.
+                    let mut branch_conditions = conditions.to_vec();
+                    for (expression, branch_lines) in &branches.exprs {
+                        if expression == "else" {
+                            self.walk(branch_lines, &branch_conditions);
+                        } else {
+                            let conditions = with_condition(&branch_conditions, expression);
+                            self.walk(branch_lines, &conditions);
+                        }
+                        branch_conditions.push(negate(expression));
+                    }
+                }
+                RawLine::Choices(choices) => self.walk_choices(choices, conditions),
+                _ => (),
+            }
+        }
+    }
+
+    fn walk_choices(&mut self, choices: &'s RawChoices, conditions: &[String]) {
+        // Choices without a target use the target of the next available choice, so resolve targets backwards.
+        // Conditional choices only pass their target on while available, so a choice without a target
+        // may lead to several passages, each under the conditions for inferring it.
+        let mut targets: Vec<(&str, Option<&str>, Vec<String>)> = Vec::new();
+        let mut inferred: Vec<(&str, Vec<String>)> = Vec::new();
+        for (key, choice) in choices.into_iter().rev() {
+            let (entries, group) = match choice {
+                RawChoice::Target(target) => (vec![(key, target)], None),
+                RawChoice::Conditional(conditional) => {
+                    (conditional.iter().rev().collect(), Some(key.as_str()))
+                }
+            };
+            let choice_conditions = match group {
+                Some(group) => with_condition(conditions, group),
+                None => conditions.to_vec(),
+            };
+            for (label, target) in entries {
+                match target {
+                    ChoiceTarget::PassageName(name) => {
+                        targets.push((label, Some(name), choice_conditions.clone()));
+                        inferred = match group {
+                            Some(group) => {
+                                let negated = negate(group);
+                                let mut alternatives =
+                                    vec![(name.as_str(), vec![group.to_string()])];
+                                alternatives.extend(
+                                    inferred
+                                        .drain(..)
+                                        .filter(|(_, conditions)| {
+                                            !conditions.iter().any(|c| c == group)
+                                        })
+                                        .map(|(target, mut conditions)| {
+                                            conditions.insert(0, negated.clone());
+                                            (target, conditions)
+                                        }),
+                                );
+                                alternatives
+                            }
+                            None => vec![(name.as_str(), Vec::new())],
+                        };
+                    }
+                    ChoiceTarget::None => {
+                        for (target, inferred_conditions) in inferred.iter().rev() {
+                            // Skip alternatives that contradict the choice's own group.
+                            if let Some(group) = group {
+                                if inferred_conditions.contains(&negate(group)) {
+                                    continue;
+                                }
+                            }
+                            let mut conditions = choice_conditions.clone();
+                            for condition in inferred_conditions {
+                                if !conditions.contains(condition) {
+                                    conditions.push(condition.clone());
+                                }
+                            }
+                            targets.push((label, Some(target), conditions));
+                        }
+                    }
+                    ChoiceTarget::Lines(_) => {
+                        targets.push((label, None, choice_conditions.clone()))
+                    }
+                }
+            }
+        }
+        targets.reverse();
+
+        for (label, target, conditions) in targets {
+            if let Some(target) = target {
+                self.push(target, EdgeKind::Choice, Some(label), &conditions);
+            }
+        }
+        // Embedded lines are walked after the choice edges, in line order.
+        for (key, choice) in choices {
+            match choice {
+                RawChoice::Target(ChoiceTarget::Lines(lines)) => self.walk(lines, conditions),
+                RawChoice::Conditional(conditional) => {
+                    let conditions = with_condition(conditions, key);
+                    for (_label, target) in conditional {
+                        if let ChoiceTarget::Lines(lines) = target {
+                            self.walk(lines, &conditions);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        match &choices.default {
+            ChoiceTarget::PassageName(passage) => {
+                self.push(passage, EdgeKind::Timeout, None, conditions)
+            }
+            ChoiceTarget::Lines(lines) => self.walk(lines, conditions),
+            ChoiceTarget::None => (),
+        }
+    }
+}
+
+/// Sorts the global namespace first, then by namespace and passage.
+type NodeKey = (bool, String, String);
+
+fn node_key(namespace: &str, passage: &str) -> NodeKey {
+    (
+        namespace != GLOBAL,
+        namespace.to_string(),
+        passage.to_string(),
+    )
+}
+
+fn with_condition(conditions: &[String], condition: &str) -> Vec<String> {
+    let mut conditions = conditions.to_vec();
+    conditions.push(condition.to_string());
+    conditions
+}
+
+/// Gets the condition for `condition` not holding, e.g. `not ($gold > 0)` for `if $gold > 0`.
+fn negate(condition: &str) -> String {
+    let expression = condition
+        .strip_prefix("if ")
+        .or_else(|| condition.strip_prefix("elif "))
+        .unwrap_or(condition);
+    format!("not ({})", expression.trim())
+}
+
+/// Builds the graph of every passage in the story and the choices, calls and jumps between them.
+pub fn build_story_graph(story: &Story) -> StoryGraph {
+    let mut nodes: BTreeMap<NodeKey, bool> = BTreeMap::new();
+    let mut sections: Vec<(&String, &Section)> = story.sections.iter().collect();
+    sections.sort_by_key(|(namespace, _)| (namespace.as_str() != GLOBAL, *namespace));
+
+    let mut edges = Vec::new();
+    for (namespace, section) in sections {
+        // The runner runs the `onExit` of the namespace and its parents on return.
+        let has_on_exit = resolution_order(namespace)
+            .into_iter()
+            .filter_map(|namespace| story.sections.get(namespace))
+            .any(|section| section.on_exit().is_some());
+        let mut passages: Vec<(&String, &Passage)> = section.passages.iter().collect();
+        passages.sort_by_key(|(name, _)| *name);
+        for (name, passage) in passages {
+            nodes.insert(node_key(namespace, name), false);
+            let mut builder = GraphBuilder {
+                story,
+                namespace,
+                from: qualify(namespace, name),
+                flat: flatten(passage),
+                tail_calls: !has_on_exit,
+                edges: Vec::new(),
+                missing: Vec::new(),
+            };
+            builder.walk(passage, &[]);
+            edges.extend(builder.edges);
+            for key in builder.missing {
+                nodes.entry(key).or_insert(true);
+            }
+        }
+    }
+
+    StoryGraph {
+        nodes: nodes
+            .into_iter()
+            .map(|((_, namespace, passage), missing)| GraphNode {
+                id: qualify(&namespace, &passage),
+                namespace,
+                passage,
+                missing,
+            })
+            .collect(),
+        edges,
+    }
+}
+
+/// Quotes `text` as a DOT identifier.
+fn dot_string(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Renders the graph in GraphViz DOT, with a cluster per namespace.
+pub fn build_story_graph_dot(graph: &StoryGraph) -> String {
+    let mut dot = String::from("digraph Story {\n    node [shape=box];\n");
+
+    let mut namespaces: Vec<&str> = graph
+        .nodes
+        .iter()
+        .map(|node| node.namespace.as_str())
+        .collect();
+    namespaces.dedup();
+    for namespace in namespaces {
+        dot.push_str(&format!(
+            "\n    subgraph {} {{\n        label = {};\n",
+            dot_string(&format!("cluster_{}", namespace)),
+            dot_string(namespace)
+        ));
+        for node in graph
+            .nodes
+            .iter()
+            .filter(|node| node.namespace == namespace)
+        {
+            let mut attributes = vec![format!("label = {}", dot_string(&node.passage))];
+            if node.missing {
+                attributes.push("style = dashed".to_string());
+                attributes.push("color = red".to_string());
+            }
+            dot.push_str(&format!(
+                "        {} [{}];\n",
+                dot_string(&node.id),
+                attributes.join(", ")
+            ));
+        }
+        dot.push_str("    }\n");
+    }
+
+    if !graph.edges.is_empty() {
+        dot.push('\n');
+    }
+    for edge in &graph.edges {
+        let mut label: Vec<String> = edge.label.iter().cloned().collect();
+        label.extend(
+            edge.conditions
+                .iter()
+                .map(|condition| format!("[{}]", condition)),
+        );
+        let mut attributes = Vec::new();
+        if !label.is_empty() {
+            attributes.push(format!("label = {}", dot_string(&label.join("\n"))));
+        }
+        match edge.kind {
+            EdgeKind::Choice => (),
+            EdgeKind::Timeout => attributes.push("style = dotted".to_string()),
+            EdgeKind::Call => attributes.push("style = dashed".to_string()),
+            EdgeKind::Goto => attributes.push("style = bold".to_string()),
+        }
+        dot.push_str(&format!(
+            "    {} -> {}",
+            dot_string(&edge.from),
+            dot_string(&edge.to)
+        ));
+        if !attributes.is_empty() {
+            dot.push_str(&format!(" [{}]", attributes.join(", ")));
+        }
+        dot.push_str(";\n");
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Renders the story graph as DOT, or JSON if `path` ends in `.json`.
+pub fn build_story_graph_file(story: &Story, path: &str) -> Result<String> {
+    let graph = build_story_graph(story);
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("json") => match serde_json::to_string_pretty(&graph) {
+            Ok(json) => Ok(json + "\n"),
+            Err(err) => Err(error!("{}", err)),
+        },
+        _ => Ok(build_story_graph_dot(&graph)),
+    }
+}
+
+fn try_story_graph(path: &str) -> Result<()> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_ref() {
+            let graph = build_story_graph_file(runner.story(), path)?;
+            if let Err(err) = fs::write(path, graph) {
+                return Err(error!("Error writing story graph to '{}': {}", path, err));
+            }
+            Ok(())
+        } else {
+            Err(error!("Story was none."))
+        }
+    }
+}
+/// Writes the story graph as GraphViz DOT, or JSON if `path` ends in `.json`.
+#[no_mangle]
+pub extern "C" fn story_graph(path: *const c_char, length: usize) -> FFIStr {
+    let path = FFIStr::to_str(path, length);
+    FFIStr::result(try_story_graph(path))
+}
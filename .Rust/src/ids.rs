@@ -0,0 +1,248 @@
+use crate::lines::{current_raw_line, flatten, raw_text, FlatLine};
+use crate::namespaces::qualify;
+pub use crate::{ffi::FFIStr, LINE, RUNNER};
+use kataru::*;
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+static mut LINE_ID: String = String::new();
+static mut CHOICE_ID: String = String::new();
+static mut LINE_IDS: Vec<LineId> = Vec::new();
+
+/// A stable identifier for a line of text or a choice label.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineId {
+    pub id: String,
+    pub namespace: String,
+    pub passage: String,
+    /// Index of the line in the runner's flattened passage.
+    pub line: usize,
+    /// The choice label, if this identifies a choice.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// Source text with any id annotation removed.
+    pub text: String,
+    /// True if the id was annotated by the writer rather than hashed.
+    pub explicit: bool,
+}
+
+/// Marks an explicit line id at the end of a line, e.g. `Hello there. #id:greeting`.
+pub const LINE_ID_MARKER: &str = "#id:";
+
+/// Splits a trailing ` #id:name` annotation off of `text`.
+pub fn split_line_id(text: &str) -> (&str, Option<&str>) {
+    if let Some((rest, id)) = text.trim_end().rsplit_once(LINE_ID_MARKER) {
+        let valid_id = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if valid_id && (rest.is_empty() || rest.ends_with(char::is_whitespace)) {
+            return (rest.trim_end(), Some(id));
+        }
+    }
+    (text, None)
+}
+
+/// 64 bit FNV-1a, which unlike `DefaultHasher` is stable across platforms.
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Returns the labels of every choice in `choices`, including conditional ones.
+fn choice_labels(choices: &RawChoices) -> Vec<&str> {
+    let mut labels = Vec::new();
+    for (key, choice) in choices {
+        match choice {
+            RawChoice::Target(_) => labels.push(key.as_str()),
+            RawChoice::Conditional(conditional) => {
+                labels.extend(conditional.keys().map(String::as_str))
+            }
+        }
+    }
+    labels
+}
+
+/// Builds ids for every text line and choice label in a passage, in line order.
+/// Lines with identical content get an occurrence suffix, e.g. `-2`.
+pub fn passage_line_ids(namespace: &str, passage_name: &str, passage: &Passage) -> Vec<LineId> {
+    let mut ids = Vec::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut push = |line: usize, choice: Option<&str>, speaker: Option<&str>, source: &str| {
+        let (text, explicit_id) = split_line_id(source);
+        let id = match explicit_id {
+            Some(id) => id.to_string(),
+            None => {
+                let kind = if choice.is_some() { "choice" } else { "line" };
+                let hash = fnv1a(
+                    [namespace, passage_name, kind, speaker.unwrap_or(""), text]
+                        .iter()
+                        .flat_map(|part| part.bytes().chain(std::iter::once(0))),
+                );
+                let hash = format!("{:016x}", hash);
+                let count = occurrences.entry(hash.clone()).or_insert(0);
+                *count += 1;
+                match *count {
+                    1 => hash,
+                    n => format!("{}-{}", hash, n),
+                }
+            }
+        };
+        ids.push(LineId {
+            id,
+            namespace: namespace.to_string(),
+            passage: passage_name.to_string(),
+            line,
+            choice: choice.map(str::to_string),
+            speaker: speaker.map(str::to_string),
+            text: text.to_string(),
+            explicit: explicit_id.is_some(),
+        });
+    };
+    for (line, flat_line) in flatten(passage).into_iter().enumerate() {
+        let FlatLine::Raw(raw) = flat_line else {
+            continue;
+        };
+        match raw {
+            RawLine::Dialogue(dialogue) => {
+                if let Some((speaker, text)) = dialogue.iter().next() {
+                    push(line, None, Some(speaker), text);
+                }
+            }
+            RawLine::Text(text) => push(line, None, None, text),
+            RawLine::Choices(choices) => {
+                for label in choice_labels(choices) {
+                    push(line, Some(label), None, label);
+                }
+            }
+            _ => (),
+        }
+    }
+    ids
+}
+
+/// Builds ids for every line in the story, ordered by namespace and passage.
+pub fn build_line_ids(story: &Story) -> Vec<LineId> {
+    let mut namespaces: Vec<&String> = story.sections.keys().collect();
+    namespaces.sort();
+    let mut ids = Vec::new();
+    for namespace in namespaces {
+        let passages = &story.sections[namespace].passages;
+        let mut passage_names: Vec<&String> = passages.keys().collect();
+        passage_names.sort();
+        for passage_name in passage_names {
+            ids.extend(passage_line_ids(
+                namespace,
+                passage_name,
+                &passages[passage_name],
+            ));
+        }
+    }
+    ids
+}
+
+/// Checks that no two lines in the story share an id.
+pub fn validate_line_ids(story: &Story) -> Result<()> {
+    let mut seen: HashMap<String, LineId> = HashMap::new();
+    for line_id in build_line_ids(story) {
+        match seen.entry(line_id.id.clone()) {
+            Entry::Occupied(entry) => {
+                let first = entry.get();
+                return Err(error!(
+                    "Line id '{}' in passage '{}' is already used in passage '{}'.",
+                    line_id.id,
+                    qualify(&line_id.namespace, &line_id.passage),
+                    qualify(&first.namespace, &first.passage)
+                ));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(line_id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Source text of the runner's current line with its id annotation removed.
+pub fn current_source_text(runner: &Runner) -> Option<&str> {
+    let text = current_raw_line(runner).and_then(raw_text)?;
+    Some(split_line_id(text).0)
+}
+
+/// Gets the ids of the runner's current line and its choices.
+/// Ids depend on the lines before them, so this hashes the whole passage.
+fn current_line_ids(runner: &Runner) -> Vec<LineId> {
+    let Some(passage) = runner
+        .story()
+        .sections
+        .get(runner.namespace())
+        .and_then(|section| section.passages.get(runner.passage()))
+    else {
+        return Vec::new();
+    };
+    passage_line_ids(runner.namespace(), runner.passage(), passage)
+        .into_iter()
+        .filter(|line_id| line_id.line == runner.line())
+        .collect()
+}
+
+/// Finds the id of the line, or of one of its choices, in `line_ids`.
+pub fn find_line_id<'a>(line_ids: &'a [LineId], choice: Option<&str>) -> Option<&'a str> {
+    line_ids
+        .iter()
+        .find(|line_id| line_id.choice.as_deref() == choice)
+        .map(|line_id| line_id.id.as_str())
+}
+
+/// Rebuilds the ids of the current line, which must be called whenever `LINE` changes.
+pub fn load_line_ids() {
+    unsafe {
+        LINE_IDS = match (RUNNER.as_ref(), &LINE) {
+            (Some(runner), Line::Dialogue(_) | Line::Choices(_)) => current_line_ids(runner),
+            _ => Vec::new(),
+        };
+    }
+}
+
+/// Gets the ids of the current line and its choices, as of the last `load_line_ids`.
+pub fn line_ids() -> &'static [LineId] {
+    unsafe { &LINE_IDS }
+}
+
+/// Gets the stable id of the current dialogue or text line, or an empty string if it has none.
+#[no_mangle]
+pub extern "C" fn get_line_id() -> FFIStr {
+    unsafe {
+        LINE_ID = match &LINE {
+            Line::Dialogue(_) => find_line_id(line_ids(), None)
+                .unwrap_or_default()
+                .to_string(),
+            _ => String::new(),
+        };
+        FFIStr::from(&LINE_ID)
+    }
+}
+
+/// Gets the stable id of the i-th choice of the current line, or an empty string if it has none.
+#[no_mangle]
+pub extern "C" fn get_choice_id(i: usize) -> FFIStr {
+    unsafe {
+        CHOICE_ID = match &LINE {
+            Line::Choices(choices) => choices
+                .choices
+                .get(i)
+                .and_then(|label| find_line_id(line_ids(), Some(label)))
+                .unwrap_or_default()
+                .to_string(),
+            _ => String::new(),
+        };
+        FFIStr::from(&CHOICE_ID)
+    }
+}
@@ -0,0 +1,246 @@
+use crate::lines::{flatten, raw_text, FlatLine};
+use crate::namespaces::{qualify, resolve};
+use kataru::*;
+
+static mut STRICT_INTERPOLATION: bool = false;
+
+/// Number formatting from a `:,.2` style spec.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct NumberFormat {
+    grouping: bool,
+    precision: Option<usize>,
+}
+impl NumberFormat {
+    /// Parses a spec of the form `[,][.N]`, returning None if `spec` isn't one.
+    fn parse(spec: &str) -> Option<Self> {
+        let (grouping, spec) = match spec.strip_prefix(',') {
+            Some(spec) => (true, spec),
+            None => (false, spec),
+        };
+        let precision = match spec {
+            "" if grouping => None,
+            _ => Some(spec.strip_prefix('.')?.parse().ok()?),
+        };
+        Some(Self {
+            grouping,
+            precision,
+        })
+    }
+
+    fn format(&self, number: f64) -> String {
+        let formatted = match self.precision {
+            Some(precision) => format!("{:.*}", precision, number),
+            None => format!("{}", number),
+        };
+        if !self.grouping {
+            return formatted;
+        }
+        let (sign, digits) = match formatted.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", formatted.as_str()),
+        };
+        let (int, frac) = match digits.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (digits, None),
+        };
+        let mut grouped = String::with_capacity(formatted.len() + int.len() / 3);
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        match frac {
+            Some(frac) => format!("{}{}.{}", sign, grouped, frac),
+            None => format!("{}{}", sign, grouped),
+        }
+    }
+}
+
+/// A `${var[:format][|one|other]}` reference in dialogue text.
+#[derive(Debug, PartialEq)]
+struct Reference<'t> {
+    var: &'t str,
+    format: NumberFormat,
+    forms: Vec<&'t str>,
+}
+impl<'t> Reference<'t> {
+    fn parse(body: &'t str) -> Option<Self> {
+        let mut parts = body.split('|');
+        let mut var = parts.next()?.trim();
+        let forms: Vec<&str> = parts.collect();
+        let mut format = NumberFormat::default();
+        // Since namespaces are also separated by ':', only treat the suffix as a format if it parses.
+        if let Some((name, spec)) = var.rsplit_once(':') {
+            if let Some(parsed) = NumberFormat::parse(spec) {
+                var = name;
+                format = parsed;
+            }
+        }
+        if var.is_empty() || forms.len() == 1 || forms.len() > 3 {
+            return None;
+        }
+        Some(Self { var, format, forms })
+    }
+
+    /// Formats `value` according to this reference.
+    fn format(&self, value: &Value) -> String {
+        let formatted = match value {
+            Value::Number(number) => self.format.format(*number),
+            value => value.to_string(),
+        };
+        let form = match (self.forms.as_slice(), value) {
+            ([], _) => return formatted,
+            ([zero, _one, _other], Value::Number(n)) if *n == 0.0 => zero,
+            ([one, _other] | [_, one, _other], Value::Number(n)) if *n == 1.0 => one,
+            ([.., other], _) => other,
+        };
+        form.replace('#', &formatted)
+    }
+}
+
+/// Calls `f` on each `${...}` reference in `text`, replacing it with the returned string.
+fn replace_references<F>(text: &str, mut f: F) -> String
+where
+    F: FnMut(&str, Option<Reference>) -> String,
+{
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let body = &rest[start + "${".len()..start + len];
+        result.push_str(&rest[..start]);
+        result.push_str(&f(body, Reference::parse(body)));
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Values are swapped for private use characters from here on while the text is parsed, so their markup isn't.
+const VALUE_SENTINEL_BASE: u32 = 0x100000;
+
+/// Text with its interpolated values swapped for sentinels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Interpolated {
+    pub text: String,
+    values: Vec<String>,
+}
+
+impl Interpolated {
+    /// Expands `${var}`, `${gold:,.2}` and `${coins|no coins|# coin|# coins}` references in `text` using `lookup`.
+    /// Unknown variables are left in the text as written.
+    pub fn mark<'a, F>(text: &str, lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<&'a Value>,
+    {
+        let mut values = Vec::new();
+        let text = replace_references(text, |body, reference| {
+            match reference.and_then(|reference| Some((lookup(reference.var)?, reference))) {
+                Some((value, reference)) => {
+                    let value = reference.format(value);
+                    match char::from_u32(VALUE_SENTINEL_BASE + values.len() as u32) {
+                        Some(sentinel) => {
+                            values.push(value);
+                            sentinel.to_string()
+                        }
+                        None => value,
+                    }
+                }
+                // Escape braces so Kataru's own `{...}` expressions leave the reference alone.
+                None => format!("${{{{{}}}}}", body),
+            }
+        });
+        Self { text, values }
+    }
+
+    /// Swaps the sentinels in processed `text` back for their values.
+    /// Also returns the new character offset of each character offset in `text`, up to its length.
+    pub fn resolve(&self, text: &str) -> (String, Vec<usize>) {
+        let mut resolved = String::with_capacity(text.len());
+        let mut offsets = Vec::with_capacity(text.len() + 1);
+        let mut offset = 0;
+        for c in text.chars() {
+            offsets.push(offset);
+            let value = (c as u32)
+                .checked_sub(VALUE_SENTINEL_BASE)
+                .and_then(|i| self.values.get(i as usize));
+            match value {
+                Some(value) => {
+                    resolved.push_str(value);
+                    offset += value.chars().count();
+                }
+                None => {
+                    resolved.push(c);
+                    offset += 1;
+                }
+            }
+        }
+        offsets.push(offset);
+        (resolved, offsets)
+    }
+}
+
+/// Returns true if `var` is a declared variable as seen from `namespace`.
+/// `passage.var` and `character.var` resolve to `$passage.var` and `$character.var` declarations.
+fn is_declared(story: &Story, namespace: &str, var: &str) -> bool {
+    let declared = |name: &str| resolve(story, namespace, name, |s, name| s.value(name)).is_ok();
+    if declared(var) {
+        return true;
+    }
+    match var.split_once('.') {
+        Some((_prefix, suffix)) => {
+            declared(&format!("$passage.{}", suffix)) || declared(&format!("$character.{}", suffix))
+        }
+        None => false,
+    }
+}
+
+/// Checks that every `${...}` reference in the story's dialogue is well formed and declared.
+pub fn validate_interpolation(story: &Story) -> Result<()> {
+    let mut namespaces: Vec<&String> = story.sections.keys().collect();
+    namespaces.sort();
+    for namespace in namespaces {
+        let section = &story.sections[namespace];
+        let mut passage_names: Vec<&String> = section.passages.keys().collect();
+        passage_names.sort();
+        for passage_name in passage_names {
+            for line in flatten(&section.passages[passage_name]) {
+                let FlatLine::Raw(line) = line else { continue };
+                let Some(text) = raw_text(line) else { continue };
+                let mut error = None;
+                replace_references(text, |body, reference| {
+                    match reference {
+                        None => error = Some(format!("Invalid variable reference '${{{}}}'", body)),
+                        Some(reference) if !is_declared(story, namespace, reference.var) => {
+                            error = Some(format!("Unknown variable '{}'", reference.var))
+                        }
+                        _ => (),
+                    }
+                    String::new()
+                });
+                if let Some(error) = error {
+                    return Err(error!(
+                        "Passage '{}': {}",
+                        qualify(namespace, passage_name),
+                        error
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if unknown variables should be reported when validating.
+pub fn strict_interpolation() -> bool {
+    unsafe { STRICT_INTERPOLATION }
+}
+
+/// Enables reporting unknown `${var}` references as validation errors.
+#[no_mangle]
+pub extern "C" fn set_strict_interpolation(strict: bool) {
+    unsafe { STRICT_INTERPOLATION = strict }
+}
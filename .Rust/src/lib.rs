@@ -1,8 +1,45 @@
 mod ffi;
 pub use ffi::FFIStr;
 
+mod lines;
+
+mod richtext;
+pub use richtext::{RichSpan, RichText};
+
+mod ids;
+pub use ids::{
+    build_line_ids, get_choice_id, get_line_id, split_line_id, validate_line_ids, LineId,
+    LINE_ID_MARKER,
+};
+
+mod locale;
+pub use locale::{
+    build_missing_translations, build_string_table_csv, build_string_table_xliff,
+    extract_string_table, get_missing_translations, load_locale_table, set_locale, StringTable,
+};
+
+mod voice;
+pub use voice::{
+    build_voice_manifest, build_voice_manifest_csv, get_voice_clip_key, voice_manifest, VoiceLine,
+};
+
+mod interpolation;
+pub use interpolation::{set_strict_interpolation, validate_interpolation};
+
+mod graph;
+pub use graph::{
+    build_story_graph, build_story_graph_dot, build_story_graph_file, story_graph, EdgeKind,
+    GraphEdge, GraphNode, StoryGraph,
+};
+
+mod timeline;
+pub use timeline::{Markers, Timeline, TimelineEvent};
+
 mod dialogue;
-pub use dialogue::{get_attributes, get_speaker, get_speech};
+pub use dialogue::{
+    get_attributes, get_rich_text_spans, get_speaker, get_speech, get_timeline, set_rich_text,
+    set_rich_text_passthrough, Speech, SpeechOptions,
+};
 
 mod bookmark;
 pub use bookmark::{
@@ -12,16 +49,42 @@ pub use bookmark::{
 
 mod story;
 use kataru::{Line, Runner};
-pub use story::{init_runner, next, save_story, validate};
+pub use story::{choose, choose_timeout, init_runner, next, save_story, validate};
 
 mod choices;
-pub use choices::{get_choice, get_choices, get_timeout};
+pub use choices::{
+    build_choice_details, get_choice, get_choice_details, get_choices, get_timeout,
+    get_timeout_default, take_timeout_default, ChoiceDetails, TimeoutDefault,
+};
 
 mod commands;
-pub use commands::{get_command, get_params};
+pub use commands::{
+    build_command_list, build_handler_report, check_handlers, complete, complete_command,
+    get_command, get_command_base_name, get_command_character, get_command_qualified_name,
+    get_param_bool, get_param_count, get_param_name, get_param_number, get_param_string,
+    get_param_type, get_params, list_commands, validate_command_results, CommandName,
+    HandlerReport, ParamType, RESULT_PARAM,
+};
+
+mod namespaces;
+
+mod sources;
+
+mod characters;
+pub use characters::{
+    build_character_config, build_character_list, get_character_config, list_characters,
+    load_character_configs, CharacterConfigs,
+};
 
 mod codegen;
-pub use codegen::{build_codegen_consts, codegen_consts};
+pub use codegen::{
+    build_codegen, build_codegen_consts, build_codegen_handlers, build_codegen_split,
+    codegen_c_header, codegen_changed_files, codegen_consts, codegen_handlers, codegen_json,
+    codegen_typescript, set_codegen_nested, set_codegen_split, set_codegen_template, write_codegen,
+    Backend, CBackend, CSharpBackend, CodegenOptions, CommandSymbol, JsonBackend, Param,
+    SplitCodegen, StateSymbol, Symbol, Symbols, TemplateBackend, TypeScriptBackend,
+    DEFAULT_TEMPLATE,
+};
 
 /// Global static mutable variables.
 pub static mut RUNNER: Option<Runner> = None;
@@ -0,0 +1,100 @@
+use kataru::*;
+
+/// A line in a passage's flattened line array.
+/// Mirrors the runner's internal layout so bookmark line numbers can be mapped back onto raw lines.
+#[derive(Debug, Clone, Copy)]
+pub enum FlatLine<'s> {
+    Raw(&'s RawLine),
+    Break,
+    Return,
+}
+
+/// Flattens `lines` the same way the runner does, with nested branches and
+/// embedded choice passages inlined and separated by breaks.
+fn flatten_lines<'s>(lines: &'s [RawLine], flat: &mut Vec<FlatLine<'s>>) {
+    for line in lines {
+        flat.push(FlatLine::Raw(line));
+        match line {
+            RawLine::Branches(branches) => {
+                for (_expression, branch_lines) in &branches.exprs {
+                    flatten_lines(branch_lines, flat);
+                    flat.push(FlatLine::Break);
+                }
+                // Remove the last break, since it's redundant.
+                flat.pop();
+            }
+            RawLine::Choices(choices) => {
+                let mut flatten_target = |target: &'s ChoiceTarget| {
+                    if let ChoiceTarget::Lines(lines) = target {
+                        flatten_lines(lines, flat);
+                        flat.push(FlatLine::Break);
+                    }
+                };
+                for (_key, choice) in choices {
+                    match choice {
+                        RawChoice::Target(target) => flatten_target(target),
+                        RawChoice::Conditional(conditional) => {
+                            for (_inner_key, target) in conditional {
+                                flatten_target(target)
+                            }
+                        }
+                    }
+                }
+
+                // Remove the last break, since it's redundant.
+                if let Some(FlatLine::Break) = flat.last() {
+                    flat.pop();
+                }
+
+                if let ChoiceTarget::Lines(lines) = &choices.default {
+                    flatten_lines(lines, flat)
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Flattens a passage into the line array the runner indexes with bookmark line numbers.
+pub fn flatten(passage: &Passage) -> Vec<FlatLine<'_>> {
+    let mut flat = Vec::new();
+    flatten_lines(passage, &mut flat);
+    match flat.last() {
+        Some(FlatLine::Raw(RawLine::Return(_))) => (),
+        _ => flat.push(FlatLine::Return),
+    }
+    flat
+}
+
+/// Gets the raw line at `line` in the given passage, if it exists.
+pub fn raw_line<'s>(
+    story: &'s Story,
+    namespace: &str,
+    passage: &str,
+    line: usize,
+) -> Option<&'s RawLine> {
+    let passage = story.sections.get(namespace)?.passages.get(passage)?;
+    match flatten(passage).get(line) {
+        Some(FlatLine::Raw(raw)) => Some(raw),
+        _ => None,
+    }
+}
+
+/// Gets the raw line the runner is currently on.
+pub fn current_raw_line(runner: &Runner) -> Option<&RawLine> {
+    raw_line(
+        runner.story(),
+        runner.namespace(),
+        runner.passage(),
+        runner.line(),
+    )
+}
+
+/// Gets the unprocessed source text of a dialogue or text line.
+pub fn raw_text(line: &RawLine) -> Option<&str> {
+    match line {
+        RawLine::Dialogue(dialogue) => dialogue.values().next().map(String::as_str),
+        RawLine::Text(text) => Some(text),
+        _ => None,
+    }
+}
@@ -0,0 +1,335 @@
+use crate::dialogue::load_speech;
+use crate::ffi::json_or_error;
+use crate::ids::{build_line_ids, find_line_id, split_line_id, LineId};
+pub use crate::{ffi::FFIStr, RUNNER};
+use kataru::*;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::BTreeMap;
+use std::{fs, os::raw::c_char, path::Path};
+
+static mut LOCALE: String = String::new();
+static mut LOCALE_TABLES: BTreeMap<String, StringTable> = BTreeMap::new();
+static mut MISSING_TRANSLATIONS_JSON: String = String::new();
+
+/// Translations keyed by stable line id.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StringTable {
+    pub translations: BTreeMap<String, String>,
+}
+
+/// Quotes a CSV field if it contains separators, quotes or newlines.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => (),
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_error(err: impl std::fmt::Display) -> Error {
+    error!("Invalid XLIFF: {}", err)
+}
+
+impl StringTable {
+    /// Parses a table with `id` and `translation` columns. Empty translations are skipped.
+    pub fn from_csv(text: &str) -> Result<Self> {
+        let mut records = parse_csv(text).into_iter();
+        let header = records.next().unwrap_or_default();
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|column| column.trim() == name)
+                .ok_or_else(|| error!("String table is missing the '{}' column.", name))
+        };
+        let (id_column, translation_column) = (column("id")?, column("translation")?);
+        let mut table = Self::default();
+        for record in records {
+            if let (Some(id), Some(translation)) =
+                (record.get(id_column), record.get(translation_column))
+            {
+                if !id.is_empty() && !translation.is_empty() {
+                    table.translations.insert(id.clone(), translation.clone());
+                }
+            }
+        }
+        Ok(table)
+    }
+
+    /// Parses the `<target>` of each XLIFF 1.2 `<trans-unit>`. Empty targets are skipped.
+    pub fn from_xliff(text: &str) -> Result<Self> {
+        let mut table = Self::default();
+        let mut reader = Reader::from_str(text);
+        let mut unit_id: Option<String> = None;
+        let mut target: Option<String> = None;
+        loop {
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(element) if element.local_name().as_ref() == b"trans-unit" => {
+                    let id = element
+                        .try_get_attribute("id")
+                        .map_err(xml_error)?
+                        .ok_or_else(|| error!("XLIFF trans-unit is missing an id."))?;
+                    unit_id = Some(id.unescape_value().map_err(xml_error)?.into_owned());
+                }
+                Event::End(element) if element.local_name().as_ref() == b"trans-unit" => {
+                    unit_id = None;
+                }
+                Event::Start(element)
+                    if unit_id.is_some() && element.local_name().as_ref() == b"target" =>
+                {
+                    target = Some(String::new());
+                }
+                Event::End(element) if element.local_name().as_ref() == b"target" => {
+                    if let (Some(id), Some(target)) = (&unit_id, target.take()) {
+                        if !target.is_empty() {
+                            table.translations.insert(id.clone(), target);
+                        }
+                    }
+                }
+                Event::Start(element) | Event::Empty(element) if target.is_some() => {
+                    return Err(error!(
+                        "XLIFF trans-unit '{}' has an unsupported <{}> element in its target.",
+                        unit_id.unwrap_or_default(),
+                        String::from_utf8_lossy(element.local_name().as_ref())
+                    ));
+                }
+                Event::Text(text) => {
+                    if let Some(target) = &mut target {
+                        target.push_str(&text.unescape().map_err(xml_error)?);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(target) = &mut target {
+                        target.push_str(&String::from_utf8_lossy(&data));
+                    }
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        Ok(table)
+    }
+
+    /// Loads a CSV or XLIFF table depending on the file extension.
+    pub fn load(path: &str) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => return Err(error!("Error reading string table '{}': {}", path, err)),
+        };
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("xlf") | Some("xliff") => Self::from_xliff(&text),
+            _ => Self::from_csv(&text),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.translations.get(id).map(String::as_str)
+    }
+}
+
+/// Builds a CSV string table of every dialogue text and choice label in the story.
+pub fn build_string_table_csv(story: &Story) -> String {
+    let mut csv = String::from("id,namespace,passage,speaker,source,translation\n");
+    for line_id in build_line_ids(story) {
+        let fields = [
+            line_id.id.as_str(),
+            &line_id.namespace,
+            &line_id.passage,
+            line_id.speaker.as_deref().unwrap_or(""),
+            &line_id.text,
+            "",
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Builds an XLIFF 1.2 string table of every dialogue text and choice label in the story.
+pub fn build_string_table_xliff(story: &Story, source_locale: &str) -> String {
+    let mut units = String::new();
+    for line_id in build_line_ids(story) {
+        let context = match &line_id.speaker {
+            Some(speaker) => format!("{}:{} ({})", line_id.namespace, line_id.passage, speaker),
+            None => format!("{}:{}", line_id.namespace, line_id.passage),
+        };
+        units.push_str(&format!(
+            r#"      <trans-unit id="{}">
+        <source>{}</source>
+        <note>{}</note>
+      </trans-unit>
+"#,
+            xml_escape(&line_id.id),
+            xml_escape(&line_id.text),
+            xml_escape(&context)
+        ));
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+  <file original="kataru" source-language="{}" datatype="plaintext">
+    <body>
+{}    </body>
+  </file>
+</xliff>
+"#,
+        xml_escape(source_locale),
+        units
+    )
+}
+
+/// Lists the lines in the story that `table` has no translation for.
+pub fn build_missing_translations(story: &Story, table: &StringTable) -> Vec<LineId> {
+    build_line_ids(story)
+        .into_iter()
+        .filter(|line_id| table.get(&line_id.id).is_none())
+        .collect()
+}
+
+/// Gets the string table for the current locale, if one was loaded.
+pub fn active_table() -> Option<&'static StringTable> {
+    unsafe { LOCALE_TABLES.get(&LOCALE) }
+}
+
+/// Translates a line, or one of its choices, into the current locale given the line's ids.
+pub fn translate(line_ids: &[LineId], choice: Option<&str>) -> Option<&'static str> {
+    active_table()?.get(find_line_id(line_ids, choice)?)
+}
+
+/// The label shown for a choice: its translation, or the source key without its `#id:` annotation.
+pub fn choice_label<'a>(line_ids: &[LineId], choice: &'a str) -> &'a str {
+    translate(line_ids, Some(choice)).unwrap_or_else(|| split_line_id(choice).0)
+}
+
+/// Maps a displayed choice label back onto its source key.
+pub fn source_choice(line_ids: &[LineId], choices: &Choices, label: &str) -> String {
+    choices
+        .choices
+        .iter()
+        .find(|choice| choice_label(line_ids, choice) == label)
+        .map_or(label, String::as_str)
+        .to_string()
+}
+
+fn try_extract_string_table(path: &str, source_locale: &str) -> Result<()> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_ref() {
+            let table = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+                Some("xlf") | Some("xliff") => {
+                    build_string_table_xliff(runner.story(), source_locale)
+                }
+                _ => build_string_table_csv(runner.story()),
+            };
+            if let Err(err) = fs::write(path, table) {
+                return Err(error!("Error writing string table to '{}': {}", path, err));
+            }
+            Ok(())
+        } else {
+            Err(error!("Story was none."))
+        }
+    }
+}
+/// Writes a string table for translators as CSV, or XLIFF if `path` ends in `.xlf` or `.xliff`.
+#[no_mangle]
+pub extern "C" fn extract_string_table(
+    path: *const c_char,
+    path_length: usize,
+    source_locale: *const c_char,
+    source_locale_length: usize,
+) -> FFIStr {
+    FFIStr::result(try_extract_string_table(
+        FFIStr::to_str(path, path_length),
+        FFIStr::to_str(source_locale, source_locale_length),
+    ))
+}
+
+fn try_load_locale_table(locale: &str, path: &str) -> Result<()> {
+    let table = StringTable::load(path)?;
+    unsafe {
+        LOCALE_TABLES.insert(locale.to_string(), table);
+    }
+    load_speech()
+}
+/// Loads the CSV or XLIFF string table for `locale`.
+#[no_mangle]
+pub extern "C" fn load_locale_table(
+    locale: *const c_char,
+    locale_length: usize,
+    path: *const c_char,
+    path_length: usize,
+) -> FFIStr {
+    FFIStr::result(try_load_locale_table(
+        FFIStr::to_str(locale, locale_length),
+        FFIStr::to_str(path, path_length),
+    ))
+}
+
+/// Sets the locale used for speech and choices.
+#[no_mangle]
+pub extern "C" fn set_locale(locale: *const c_char, length: usize) -> FFIStr {
+    let locale = FFIStr::to_str(locale, length);
+    unsafe { LOCALE = locale.to_string() }
+    FFIStr::result(load_speech())
+}
+
+fn try_missing_translations(locale: &str) -> Result<String> {
+    unsafe {
+        let Some(runner) = RUNNER.as_ref() else {
+            return Err(error!("Runner was not initialized."));
+        };
+        let Some(table) = LOCALE_TABLES.get(locale) else {
+            return Err(error!(
+                "No string table was loaded for locale '{}'.",
+                locale
+            ));
+        };
+        let missing = build_missing_translations(runner.story(), table);
+        serde_json::to_string(&missing).map_err(|err| error!("{}", err))
+    }
+}
+/// Returns a JSON list of the lines missing a translation in `locale`.
+#[no_mangle]
+pub extern "C" fn get_missing_translations(locale: *const c_char, length: usize) -> FFIStr {
+    let locale = FFIStr::to_str(locale, length);
+    unsafe {
+        MISSING_TRANSLATIONS_JSON = json_or_error(try_missing_translations(locale));
+        FFIStr::from(&MISSING_TRANSLATIONS_JSON)
+    }
+}
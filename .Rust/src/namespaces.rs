@@ -0,0 +1,59 @@
+use kataru::*;
+
+/// Splits `name` into its namespace and unqualified name.
+/// If `name` has no explicit namespace, it belongs to `namespace`.
+pub fn split_qualified<'a>(namespace: &'a str, name: &'a str) -> (&'a str, &'a str) {
+    match name.rsplit_once(':') {
+        Some((explicit_namespace, name)) => (explicit_namespace, name),
+        None => (namespace, name),
+    }
+}
+
+/// Formats `name` as seen from outside `namespace`.
+/// Global names are left unqualified.
+pub fn qualify(namespace: &str, name: &str) -> String {
+    if namespace == GLOBAL {
+        name.to_string()
+    } else {
+        format!("{}:{}", namespace, name)
+    }
+}
+
+/// Returns the namespaces searched when resolving an identifier in `namespace`,
+/// innermost first and always ending with the global namespace.
+pub fn resolution_order(namespace: &str) -> Vec<&str> {
+    let mut order = Vec::new();
+    if namespace != GLOBAL {
+        let mut parent = namespace;
+        order.push(parent);
+        while let Some((next, _)) = parent.rsplit_once(':') {
+            order.push(next);
+            parent = next;
+        }
+    }
+    order.push(GLOBAL);
+    order
+}
+
+/// Resolves `name` from `namespace` the same way Kataru does, returning the
+/// namespace the identifier was found in along with the data from `getter`.
+pub fn resolve<'s, 'n, T>(
+    story: &'s Story,
+    namespace: &'n str,
+    name: &'n str,
+    getter: fn(&'s Section, &str) -> Option<T>,
+) -> Result<(&'n str, T)> {
+    let (namespace, name) = split_qualified(namespace, name);
+    for resolved_namespace in resolution_order(namespace) {
+        let Some(section) = story.sections.get(resolved_namespace) else {
+            return Err(error!("Namespace '{}' does not exist", resolved_namespace));
+        };
+        if let Some(data) = getter(section, name) {
+            return Ok((resolved_namespace, data));
+        }
+    }
+    Err(error!(
+        "Identifier '{}' was not found in any namespaces.",
+        name
+    ))
+}
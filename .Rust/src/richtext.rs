@@ -0,0 +1,210 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A span of stripped text covered by a rich text tag.
+/// `start` and `end` are character offsets into the stripped text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RichSpan {
+    pub start: usize,
+    pub end: usize,
+    pub tag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+/// Text with its rich text tags stripped into spans.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RichText {
+    /// Text without any stripped tags.
+    pub text: String,
+    /// Spans for every stripped tag, ordered by start.
+    pub spans: Vec<RichSpan>,
+    /// Byte ranges `(start, length)` of the source that were removed.
+    removed: Vec<(usize, usize)>,
+}
+
+/// Maps common tag names onto their normalized span names.
+fn normalize_tag(name: &str) -> &str {
+    match name {
+        "b" => "bold",
+        "i" => "italic",
+        "u" => "underline",
+        "s" => "strikethrough",
+        _ => name,
+    }
+}
+
+/// Returns true if `name` looks like a tag rather than ordinary text, e.g. "a < b".
+fn is_tag_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Strips surrounding quotes from a tag value.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// A parsed tag body, e.g. `size=200%` or `/b`.
+struct Tag<'t> {
+    name: &'t str,
+    value: Option<&'t str>,
+    params: BTreeMap<String, String>,
+    closing: bool,
+    self_closing: bool,
+}
+impl<'t> Tag<'t> {
+    fn parse(body: &'t str) -> Option<Self> {
+        let (closing, body) = match body.strip_prefix('/') {
+            Some(body) => (true, body),
+            None => (false, body),
+        };
+        let (self_closing, body) = match body.strip_suffix('/') {
+            Some(body) => (true, body),
+            None => (false, body),
+        };
+
+        // Hex colors are shorthand for a color tag, e.g. <#FF0000>.
+        if let Some(hex) = body.strip_prefix('#') {
+            if closing || hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            return Some(Self {
+                name: "color",
+                value: Some(body),
+                params: BTreeMap::new(),
+                closing,
+                self_closing,
+            });
+        }
+
+        if body.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let mut tokens = body.split_whitespace();
+        let first = tokens.next()?;
+        let (name, value) = match first.split_once('=') {
+            Some((name, value)) => (name, Some(unquote(value))),
+            None => (first, None),
+        };
+        if !is_tag_name(name) || (closing && value.is_some()) {
+            return None;
+        }
+        let mut params = BTreeMap::new();
+        for token in tokens {
+            let (key, value) = token.split_once('=').unwrap_or((token, ""));
+            params.insert(key.to_string(), unquote(value).to_string());
+        }
+        Some(Self {
+            name,
+            value,
+            params,
+            closing,
+            self_closing,
+        })
+    }
+}
+
+impl RichText {
+    /// Strips tags out of `text` into spans.
+    /// Tags named in `passthrough` are left in the text for the engine to render natively.
+    /// Unclosed tags span to the end of the text, and unmatched closing tags are dropped.
+    pub fn parse<S: AsRef<str>>(text: &str, passthrough: &[S]) -> Self {
+        let mut result = Self::default();
+        let mut open: Vec<RichSpan> = Vec::new();
+        let mut chars = 0;
+        let mut rest = text;
+        let mut offset = 0;
+
+        while let Some(tag_start) = rest.find('<') {
+            let Some(tag_len) = rest[tag_start..].find('>').map(|end| end + 1) else {
+                break;
+            };
+            // A '<' before the closing '>' means the first '<' was just text.
+            if let Some(next_start) = rest[tag_start + 1..tag_start + tag_len].find('<') {
+                let literal = &rest[..tag_start + 1 + next_start];
+                result.text.push_str(literal);
+                chars += literal.chars().count();
+                offset += literal.len();
+                rest = &rest[literal.len()..];
+                continue;
+            }
+            let (before, tag_text) = (&rest[..tag_start], &rest[tag_start..tag_start + tag_len]);
+            result.text.push_str(before);
+            chars += before.chars().count();
+
+            let parsed = Tag::parse(&tag_text[1..tag_text.len() - 1]);
+            let kept = match &parsed {
+                None => true,
+                Some(tag) => passthrough.iter().any(|name| {
+                    name.as_ref() == tag.name || name.as_ref() == normalize_tag(tag.name)
+                }),
+            };
+            if kept {
+                result.text.push_str(tag_text);
+                chars += tag_text.chars().count();
+            } else if let Some(tag) = parsed {
+                result.removed.push((offset + tag_start, tag_len));
+                let name = normalize_tag(tag.name);
+                if tag.closing {
+                    if let Some(i) = open.iter().rposition(|span| span.tag == name) {
+                        let mut span = open.remove(i);
+                        span.end = chars;
+                        result.spans.push(span);
+                    }
+                } else {
+                    let span = RichSpan {
+                        start: chars,
+                        end: chars,
+                        tag: name.to_string(),
+                        value: tag.value.map(str::to_string),
+                        params: tag.params,
+                    };
+                    if tag.self_closing {
+                        result.spans.push(span);
+                    } else {
+                        open.push(span);
+                    }
+                }
+            }
+            offset += tag_start + tag_len;
+            rest = &rest[tag_start + tag_len..];
+        }
+        result.text.push_str(rest);
+        chars += rest.chars().count();
+
+        for mut span in open {
+            span.end = chars;
+            result.spans.push(span);
+        }
+        result.spans.sort_by_key(|span| span.start);
+        result
+    }
+
+    /// Maps a byte offset in the source text onto the stripped text.
+    pub fn map_byte(&self, pos: usize) -> usize {
+        let mut mapped = pos;
+        for &(start, len) in &self.removed {
+            if start >= pos {
+                break;
+            }
+            mapped -= len.min(pos - start);
+        }
+        mapped
+    }
+}
@@ -0,0 +1,81 @@
+use glob::glob;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::Path;
+
+/// A story file read without kataru's schema, keeping details the parsed `Story` drops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    /// Path of the file relative to the story directory, with `/` separators.
+    pub path: String,
+    pub namespace: String,
+    pub config: Mapping,
+    pub passages: Vec<String>,
+}
+impl SourceFile {
+    /// Gets the names declared under `field` of the config, e.g. `characters`.
+    pub fn config_keys(&self, field: &str) -> Vec<&str> {
+        match self.config.get(field) {
+            Some(Value::Mapping(mapping)) => mapping.keys().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Reads the YAML files of the story directory at `story_path`, matched the same way `Story::load` does.
+/// Files that can't be read are skipped, since loading the story reports their errors.
+pub fn load_sources(story_path: &str) -> Vec<SourceFile> {
+    let root = Path::new(story_path);
+    if !root.is_dir() {
+        return Vec::new();
+    }
+    let pattern = root.join("**/*.yml").to_string_lossy().into_owned();
+    let Ok(files) = glob(&pattern) else {
+        return Vec::new();
+    };
+    files
+        .flatten()
+        .filter_map(|file| load_source(root, &file))
+        .collect()
+}
+
+fn load_source(root: &Path, file: &Path) -> Option<SourceFile> {
+    let source = fs::read_to_string(file).ok()?.replace("\n\r---", "\n---");
+    let mut documents = source.split("\n---");
+    let config = match parse_document(documents.next()?)? {
+        Value::Mapping(config) => config,
+        _ => Mapping::new(),
+    };
+    let passages = match documents.next().map(parse_document) {
+        Some(Some(Value::Mapping(passages))) => passages
+            .keys()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(None) => return None,
+        _ => Vec::new(),
+    };
+    let namespace = config
+        .get("namespace")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let path = file
+        .strip_prefix(root)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .replace('\\', "/");
+    Some(SourceFile {
+        path,
+        namespace,
+        config,
+        passages,
+    })
+}
+
+fn parse_document(text: &str) -> Option<Value> {
+    if text.trim_start().is_empty() {
+        return Some(Value::Null);
+    }
+    serde_yaml::from_str(text).ok()
+}
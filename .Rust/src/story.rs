@@ -1,12 +1,27 @@
+use crate::characters::{init_character_configs, save_character_configs};
+use crate::choices::take_timeout_default;
+use crate::commands::{validate_command_results, RESULT_PARAM};
+use crate::dialogue::load_speech;
 pub use crate::ffi::FFIStr;
+use crate::ids::{line_ids, load_line_ids, validate_line_ids};
+use crate::interpolation::{strict_interpolation, validate_interpolation};
+use crate::locale::source_choice;
 use crate::{LINE, RUNNER};
 use kataru::*;
 use std::os::raw::c_char;
 
+static mut STORY_PATH: String = String::new();
+
+/// Returns the path the story was loaded from, or an empty string if no story is loaded.
+pub fn story_path() -> String {
+    unsafe { STORY_PATH.clone() }
+}
+
 fn try_save_story(path: &str) -> Result<()> {
     unsafe {
         if let Some(runner) = RUNNER.as_mut() {
-            runner.save_story(path)
+            runner.save_story(path)?;
+            save_character_configs(path)
         } else {
             Err(error!("Runner was not initialized."))
         }
@@ -20,11 +35,21 @@ pub extern "C" fn save_story(path: *const c_char, length: usize) -> FFIStr {
 
 fn try_init_runner(story_path: &str, bookmark_path: &str, validate: bool) -> Result<()> {
     unsafe {
-        RUNNER = Some(Runner::init(
+        let runner = Runner::init(
             Bookmark::load(bookmark_path)?,
             Story::load(story_path)?,
             validate,
-        )?);
+        )?;
+        if validate {
+            validate_line_ids(runner.story())?;
+            validate_command_results(runner.story())?;
+            if strict_interpolation() {
+                validate_interpolation(runner.story())?;
+            }
+        }
+        init_character_configs(story_path)?;
+        RUNNER = Some(runner);
+        STORY_PATH = story_path.to_string();
         Ok(())
     }
 }
@@ -46,7 +71,13 @@ pub extern "C" fn init_runner(
 fn try_validate() -> Result<()> {
     unsafe {
         if let Some(runner) = RUNNER.as_mut() {
-            runner.validate()
+            runner.validate()?;
+            validate_line_ids(runner.story())?;
+            validate_command_results(runner.story())?;
+            if strict_interpolation() {
+                validate_interpolation(runner.story())?;
+            }
+            Ok(())
         } else {
             Err(error!("Runner was not initialized."))
         }
@@ -57,11 +88,26 @@ pub extern "C" fn validate() -> FFIStr {
     FFIStr::result(try_validate())
 }
 
+/// Sets the current line, rebuilding everything derived from it.
+unsafe fn update_line(mut line: Line) -> Result<()> {
+    // The result variable is part of the declaration rather than a parameter for handlers.
+    if let Line::Command(command) = &mut line {
+        command.params.remove(RESULT_PARAM);
+    }
+    LINE = line;
+    load_line_ids();
+    load_speech()
+}
+
 fn try_next(input: &str) -> Result<()> {
     unsafe {
         if let Some(runner) = RUNNER.as_mut() {
-            LINE = runner.next(input)?;
-            Ok(())
+            // Choices may be made with displayed labels, but branching uses the source keys.
+            let input = match &LINE {
+                Line::Choices(choices) => source_choice(line_ids(), choices, input),
+                _ => input.to_string(),
+            };
+            update_line(runner.next(&input)?)
         } else {
             Err(error!("Runner was not initialized."))
         }
@@ -73,11 +119,54 @@ pub extern "C" fn next(input: *const c_char, length: usize) -> FFIStr {
     FFIStr::result(try_next(input))
 }
 
+fn try_choose(index: usize) -> Result<()> {
+    unsafe {
+        let Some(runner) = RUNNER.as_mut() else {
+            return Err(error!("Runner was not initialized."));
+        };
+        let Line::Choices(choices) = &LINE else {
+            return Err(error!("Called choose on a non-choice line."));
+        };
+        let Some(choice) = choices.choices.get(index).cloned() else {
+            return Err(error!(
+                "Invalid choice: index {} is out of range for {} choices.",
+                index,
+                choices.len()
+            ));
+        };
+        match runner.next(&choice)? {
+            Line::InvalidChoice => Err(error!("Invalid choice: '{}'.", choice)),
+            line => update_line(line),
+        }
+    }
+}
+/// Picks the choice at `index` of the current choices, regardless of its label.
+#[no_mangle]
+pub extern "C" fn choose(index: usize) -> FFIStr {
+    FFIStr::result(try_choose(index))
+}
+
+fn try_choose_timeout() -> Result<()> {
+    unsafe {
+        let Some(runner) = RUNNER.as_mut() else {
+            return Err(error!("Runner was not initialized."));
+        };
+        let Line::Choices(_) = &LINE else {
+            return Err(error!("Called choose_timeout on a non-choice line."));
+        };
+        update_line(take_timeout_default(runner)?)
+    }
+}
+/// Advances past the current choices as if their timeout ran out, taking their default.
+#[no_mangle]
+pub extern "C" fn choose_timeout() -> FFIStr {
+    FFIStr::result(try_choose_timeout())
+}
+
 fn try_read_line() -> Result<()> {
     unsafe {
         if let Some(runner) = RUNNER.as_mut() {
-            LINE = runner.read_line()?;
-            Ok(())
+            update_line(runner.read_line()?)
         } else {
             Err(error!("Runner was not initialized."))
         }
@@ -0,0 +1,121 @@
+use kataru::Value;
+use serde::Serialize;
+
+/// Markers are swapped for characters from this private use plane while the
+/// text is processed, so they survive variable replacement and tag stripping.
+const SENTINEL_BASE: u32 = 0xF0000;
+
+/// A typewriter event at a character index of the clean text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEvent {
+    pub index: usize,
+    pub event: String,
+    pub value: Value,
+}
+
+/// Parses a marker body of the form `name=value`, e.g. `pause=0.5`.
+fn parse_marker(body: &str) -> Option<(String, Value)> {
+    let (name, value) = body.split_once('=')?;
+    let valid_name =
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name || value.is_empty() || value.contains(char::is_whitespace) {
+        return None;
+    }
+    let value = match Value::from_yml(value) {
+        Ok(Value::String(_)) | Err(_) => Value::String(value.to_string()),
+        Ok(value) => value,
+    };
+    Some((name.to_string(), value))
+}
+
+/// Text with its inline markers swapped for sentinels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Markers {
+    pub text: String,
+    markers: Vec<(String, Value)>,
+}
+
+impl Markers {
+    /// Replaces each `{name=value}` marker in `text` with a sentinel.
+    /// Escaped braces (`{{`, `}}`) and expressions like `{$var + 1}` are left untouched.
+    pub fn mark(text: &str) -> Self {
+        let mut result = Self::default();
+        let mut rest = text;
+        while let Some(start) = rest.find(['{', '}']) {
+            result.text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                result.text.push_str(&rest[..2]);
+                rest = &rest[2..];
+                continue;
+            }
+            let body = rest
+                .strip_prefix('{')
+                .and_then(|body| body.find('}').map(|end| &body[..end]));
+            let sentinel = char::from_u32(SENTINEL_BASE + result.markers.len() as u32);
+            match (body, body.and_then(parse_marker), sentinel) {
+                (Some(body), Some(marker), Some(sentinel)) => {
+                    result.text.push(sentinel);
+                    result.markers.push(marker);
+                    rest = &rest[body.len() + "{}".len()..];
+                }
+                _ => {
+                    result.text.push_str(&rest[..1]);
+                    rest = &rest[1..];
+                }
+            }
+        }
+        result.text.push_str(rest);
+        result
+    }
+
+    /// Removes the sentinels from processed `text` into a timeline.
+    pub fn resolve(&self, text: &str) -> Timeline {
+        let mut timeline = Timeline::default();
+        let mut index = 0;
+        for (byte, c) in text.char_indices() {
+            let marker = (c as u32)
+                .checked_sub(SENTINEL_BASE)
+                .and_then(|i| self.markers.get(i as usize));
+            match marker {
+                Some((event, value)) => {
+                    timeline
+                        .removed
+                        .push((index + timeline.removed.len(), byte));
+                    timeline.events.push(TimelineEvent {
+                        index,
+                        event: event.clone(),
+                        value: value.clone(),
+                    });
+                }
+                None => {
+                    timeline.text.push(c);
+                    index += 1;
+                }
+            }
+        }
+        timeline
+    }
+}
+
+/// Clean text along with the events extracted from it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timeline {
+    pub text: String,
+    pub events: Vec<TimelineEvent>,
+    /// (char, byte) offsets in the processed text where sentinels were removed.
+    removed: Vec<(usize, usize)>,
+}
+
+impl Timeline {
+    /// Maps a character offset in the processed text onto the clean text.
+    pub fn map_char(&self, pos: usize) -> usize {
+        pos - self.removed.iter().filter(|(c, _)| *c < pos).count()
+    }
+
+    /// Maps a byte offset in the processed text onto the clean text.
+    pub fn map_byte(&self, pos: usize) -> usize {
+        let sentinel_len = char::from_u32(SENTINEL_BASE).unwrap().len_utf8();
+        pos - sentinel_len * self.removed.iter().filter(|(_, b)| *b < pos).count()
+    }
+}
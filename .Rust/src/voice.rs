@@ -0,0 +1,261 @@
+use crate::ids::{passage_line_ids, LineId};
+use crate::locale::csv_field;
+use crate::namespaces::{qualify, resolve, split_qualified};
+pub use crate::{ffi::FFIStr, LINE, RUNNER};
+use kataru::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::{fs, os::raw::c_char, path::Path};
+
+static mut VOICE_CLIP_KEY: String = String::new();
+
+/// A spoken line to be recorded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VoiceLine {
+    pub key: String,
+    pub id: String,
+    pub speaker: String,
+    pub namespace: String,
+    pub passage: String,
+    pub text: String,
+}
+
+/// Builds the clip key for a spoken line, e.g. `global_Start_Alice_greeting`.
+fn voice_clip_key(namespace: &str, passage: &str, speaker: &str, id: &str) -> String {
+    [namespace, passage, speaker, id]
+        .iter()
+        .map(|part| {
+            part.chars()
+                .map(|c| match c {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' => c,
+                    _ => '-',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("_")
+}
+
+/// Qualifies `speaker` as seen from `namespace`, like the runner's `get_speaker`.
+fn qualified_speaker(story: &Story, namespace: &str, speaker: &str) -> String {
+    match resolve(story, namespace, speaker, |section, name| {
+        section.character(name)
+    }) {
+        Ok((resolved_namespace, _)) => {
+            qualify(resolved_namespace, split_qualified(namespace, speaker).1)
+        }
+        Err(_) => speaker.to_string(),
+    }
+}
+
+/// Records the speaker the runner plays each narration line in `lines` as, by flattened line number.
+/// `speaker` is the last speaker before `lines`. Returns the last speaker after them,
+/// or None if it depends on which branch or choice was taken.
+fn carry_speakers<'s>(
+    lines: &'s [RawLine],
+    mut speaker: Option<&'s str>,
+    line: &mut usize,
+    speakers: &mut HashMap<usize, &'s str>,
+) -> Option<&'s str> {
+    for raw in lines {
+        let number = *line;
+        *line += 1;
+        match raw {
+            RawLine::Dialogue(dialogue) => {
+                speaker = dialogue.keys().next().map(String::as_str);
+            }
+            RawLine::Text(_) => {
+                if let Some(speaker) = speaker {
+                    speakers.insert(number, speaker);
+                }
+            }
+            RawLine::Branches(branches) => {
+                // Each branch starts from the speaker before the branches, separated by breaks.
+                let mut outcomes = Vec::new();
+                for (_expression, branch_lines) in &branches.exprs {
+                    outcomes.push(carry_speakers(branch_lines, speaker, line, speakers));
+                    *line += 1;
+                }
+                *line -= 1;
+                if !branches
+                    .exprs
+                    .iter()
+                    .any(|(expression, _)| expression == "else")
+                {
+                    outcomes.push(speaker);
+                }
+                speaker = same_speaker(&outcomes);
+            }
+            RawLine::Choices(choices) => {
+                let mut outcomes = Vec::new();
+                let mut carry_target = |target: &'s ChoiceTarget, line: &mut usize| {
+                    if let ChoiceTarget::Lines(lines) = target {
+                        outcomes.push(carry_speakers(lines, speaker, line, speakers));
+                        *line += 1;
+                    }
+                };
+                for (_key, choice) in choices {
+                    match choice {
+                        RawChoice::Target(target) => carry_target(target, line),
+                        RawChoice::Conditional(conditional) => {
+                            for (_inner_key, target) in conditional {
+                                carry_target(target, line);
+                            }
+                        }
+                    }
+                }
+                if !outcomes.is_empty() {
+                    *line -= 1;
+                }
+                if let ChoiceTarget::Lines(lines) = &choices.default {
+                    outcomes.push(carry_speakers(lines, speaker, line, speakers));
+                }
+                speaker = same_speaker(&outcomes);
+            }
+            _ => (),
+        }
+    }
+    speaker
+}
+
+/// Gets the speaker every one of `outcomes` ends with, if they agree.
+fn same_speaker<'s>(outcomes: &[Option<&'s str>]) -> Option<&'s str> {
+    let first = *outcomes.first()?;
+    outcomes
+        .iter()
+        .all(|outcome| *outcome == first)
+        .then_some(first)?
+}
+
+/// Pairs each spoken line of a passage with its qualified speaker, in line order.
+/// The runner plays lines without a speaker as the last speaker, so they're voiced by them too.
+/// Narration whose speaker depends on how it was reached is left out along with choices.
+fn spoken_lines(
+    story: &Story,
+    namespace: &str,
+    passage_name: &str,
+    passage: &Passage,
+) -> Vec<(String, LineId)> {
+    let mut speakers = HashMap::new();
+    carry_speakers(passage, None, &mut 0, &mut speakers);
+    passage_line_ids(namespace, passage_name, passage)
+        .into_iter()
+        .filter(|line_id| line_id.choice.is_none())
+        .filter_map(|line_id| {
+            let speaker = match &line_id.speaker {
+                Some(speaker) => speaker.as_str(),
+                None => speakers.get(&line_id.line)?,
+            };
+            Some((qualified_speaker(story, namespace, speaker), line_id))
+        })
+        .collect()
+}
+
+fn voice_line(speaker: String, line_id: LineId) -> VoiceLine {
+    VoiceLine {
+        key: voice_clip_key(&line_id.namespace, &line_id.passage, &speaker, &line_id.id),
+        id: line_id.id,
+        speaker,
+        namespace: line_id.namespace,
+        passage: line_id.passage,
+        text: line_id.text,
+    }
+}
+
+/// Builds the list of every spoken line in the story.
+pub fn build_voice_manifest(story: &Story) -> Vec<VoiceLine> {
+    let mut namespaces: Vec<&String> = story.sections.keys().collect();
+    namespaces.sort();
+    let mut manifest = Vec::new();
+    for namespace in namespaces {
+        let passages = &story.sections[namespace].passages;
+        let mut passage_names: Vec<&String> = passages.keys().collect();
+        passage_names.sort();
+        for passage_name in passage_names {
+            let spoken = spoken_lines(story, namespace, passage_name, &passages[passage_name]);
+            manifest.extend(
+                spoken
+                    .into_iter()
+                    .map(|(speaker, line_id)| voice_line(speaker, line_id)),
+            );
+        }
+    }
+    manifest
+}
+
+/// Builds the voice manifest as CSV.
+pub fn build_voice_manifest_csv(story: &Story) -> String {
+    let mut csv = String::from("key,id,speaker,namespace,passage,text\n");
+    for line in build_voice_manifest(story) {
+        let fields = [
+            &line.key,
+            &line.id,
+            &line.speaker,
+            &line.namespace,
+            &line.passage,
+            &line.text,
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn try_voice_manifest(path: &str) -> Result<()> {
+    unsafe {
+        if let Some(runner) = RUNNER.as_ref() {
+            let manifest = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+                Some("json") => serde_json::to_string_pretty(&build_voice_manifest(runner.story()))
+                    .map_err(|err| error!("{}", err))?,
+                _ => build_voice_manifest_csv(runner.story()),
+            };
+            if let Err(err) = fs::write(path, manifest) {
+                return Err(error!(
+                    "Error writing voice manifest to '{}': {}",
+                    path, err
+                ));
+            }
+            Ok(())
+        } else {
+            Err(error!("Story was none."))
+        }
+    }
+}
+/// Writes the voice manifest as CSV, or JSON if `path` ends in `.json`.
+#[no_mangle]
+pub extern "C" fn voice_manifest(path: *const c_char, length: usize) -> FFIStr {
+    let path = FFIStr::to_str(path, length);
+    FFIStr::result(try_voice_manifest(path))
+}
+
+/// Gets the voice clip key for the runner's current line, if it's spoken.
+fn current_voice_clip_key(runner: &Runner) -> Option<String> {
+    let passage = runner
+        .story()
+        .sections
+        .get(runner.namespace())?
+        .passages
+        .get(runner.passage())?;
+    spoken_lines(
+        runner.story(),
+        runner.namespace(),
+        runner.passage(),
+        passage,
+    )
+    .into_iter()
+    .find(|(_, line_id)| line_id.line == runner.line())
+    .map(|(speaker, line_id)| voice_line(speaker, line_id).key)
+}
+
+/// Gets the voice clip key of the current line, or an empty string if it isn't spoken.
+#[no_mangle]
+pub extern "C" fn get_voice_clip_key() -> FFIStr {
+    unsafe {
+        VOICE_CLIP_KEY = match (RUNNER.as_ref(), &LINE) {
+            (Some(runner), Line::Dialogue(_)) => current_voice_clip_key(runner).unwrap_or_default(),
+            _ => String::new(),
+        };
+        FFIStr::from(&VOICE_CLIP_KEY)
+    }
+}
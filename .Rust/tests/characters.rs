@@ -0,0 +1,126 @@
+use kataru::*;
+use kataru_ffi::{
+    build_character_config, build_character_list, get_character_config, init_runner,
+    load_character_configs, save_story, CharacterConfigs,
+};
+
+#[test]
+fn test_character_config() {
+    let story = Story::load("tests/data/story").unwrap();
+    let configs = load_character_configs("tests/data/story").unwrap();
+
+    // Global characters resolve from local namespaces.
+    assert_eq!(
+        build_character_config(&story, &configs, "Room2", "Slime").unwrap(),
+        r#"{"name":"Slime","namespace":"global","config":{"description":"The player."}}"#
+    );
+    assert_eq!(
+        build_character_config(&story, &configs, "Room1", "RedSlime").unwrap(),
+        r#"{"name":"Room1:RedSlime","namespace":"Room1","config":null}"#
+    );
+    assert_eq!(
+        build_character_config(&story, &configs, GLOBAL, "Room2:BlueSlime").unwrap(),
+        r#"{"name":"Room2:BlueSlime","namespace":"Room2","config":null}"#
+    );
+    assert!(build_character_config(&story, &configs, GLOBAL, "RedSlime").is_err());
+}
+
+#[test]
+fn test_character_list() {
+    let story = Story::load("tests/data/story").unwrap();
+    let configs = load_character_configs("tests/data/story").unwrap();
+    assert_eq!(
+        build_character_list(&story, &configs, "Room2").unwrap(),
+        concat!(
+            r#"[{"name":"Room2:BlueSlime","namespace":"Room2","config":null},"#,
+            r#"{"name":"Room2:GreenSlime","namespace":"Room2","config":null},"#,
+            r#"{"name":"GlobalLight","namespace":"global","config":null},"#,
+            r#"{"name":"Slime","namespace":"global","config":{"description":"The player."}},"#,
+            r#"{"name":"Think","namespace":"global","config":null}]"#
+        )
+    );
+    assert!(build_character_list(&story, &configs, "Room3").is_err());
+}
+
+#[test]
+fn test_character_custom_fields() {
+    let story = Story::load("tests/data/characters").unwrap();
+    let configs = load_character_configs("tests/data/characters").unwrap();
+
+    // Fields kataru doesn't parse are kept as written.
+    assert_eq!(
+        build_character_config(&story, &configs, GLOBAL, "Alice").unwrap(),
+        concat!(
+            r#"{"name":"Alice","namespace":"global","config":{"description":"The protagonist.","#,
+            r#""name":"Alice Liddell","portrait":"alice_neutral","#,
+            r#""portraits":{"happy":"alice_happy","sad":"alice_sad"}}}"#
+        )
+    );
+    // Local characters shadow global ones with their own config.
+    assert_eq!(
+        build_character_config(&story, &configs, "Room", "Alice").unwrap(),
+        r#"{"name":"Room:Alice","namespace":"Room","config":{"name":"Alice in the Room"}}"#
+    );
+    assert_eq!(
+        build_character_config(&story, &configs, "Room", "Narrator").unwrap(),
+        r#"{"name":"Narrator","namespace":"global","config":null}"#
+    );
+
+    // Without the story's sources, only the fields kataru parses are available.
+    assert_eq!(
+        build_character_config(&story, &CharacterConfigs::new(), GLOBAL, "Alice").unwrap(),
+        r#"{"name":"Alice","namespace":"global","config":{"description":"The protagonist."}}"#
+    );
+    assert!(load_character_configs("tests/data/characters/global.yml")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_character_configs_compiled() {
+    let dir = std::env::temp_dir().join("kataru_character_configs");
+    std::fs::create_dir_all(&dir).unwrap();
+    let target = dir.join("story.bin").to_string_lossy().into_owned();
+    let bookmark_path = "tests/data/bookmark.yml";
+    let init = |story_path: &str| {
+        init_runner(
+            story_path.as_ptr() as *const i8,
+            story_path.len(),
+            bookmark_path.as_ptr() as *const i8,
+            bookmark_path.len(),
+            false,
+        );
+    };
+
+    // Configs are saved alongside the compiled story, which only keeps descriptions.
+    init("tests/data/characters");
+    assert_eq!(
+        save_story(target.as_ptr() as *const i8, target.len()).as_str(),
+        ""
+    );
+    init(&target);
+    let name = "Alice";
+    assert!(get_character_config(name.as_ptr() as *const i8, name.len())
+        .as_str()
+        .contains(r#""portrait":"alice_neutral""#));
+
+    // Compiled stories without matching configs fail to load rather than losing fields.
+    let story = Story::load("tests/data/characters").unwrap();
+    let stale = dir.join("stale.bin").to_string_lossy().into_owned();
+    story.save_mp(&stale).unwrap();
+    let _ = std::fs::remove_file(format!("{}.characters.json", stale));
+    assert!(load_character_configs(&stale)
+        .unwrap_err()
+        .to_string()
+        .contains("were not found"));
+    std::fs::copy(
+        format!("{}.characters.json", target),
+        format!("{}.characters.json", stale),
+    )
+    .unwrap();
+    std::fs::write(&stale, b"changed").unwrap();
+    assert!(load_character_configs(&stale)
+        .unwrap_err()
+        .to_string()
+        .contains("different version"));
+}
@@ -0,0 +1,65 @@
+use kataru::*;
+use kataru_ffi::build_choice_details;
+
+#[test]
+fn test_choice_details() {
+    let story = Story::load("tests/data/choices").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+    runner.next("").unwrap();
+    let Line::Choices(choices) = runner.next("").unwrap() else {
+        panic!("Expected choices.");
+    };
+    assert_eq!(
+        choices.choices,
+        vec!["Left", "Look around", "Wait", "Right", "Stay"]
+    );
+
+    let details = build_choice_details(&runner, &[]).unwrap();
+    let summary: Vec<(&str, Option<&str>, bool, Option<&str>)> = details
+        .iter()
+        .map(|choice| {
+            (
+                choice.label.as_str(),
+                choice.target.as_deref(),
+                choice.available,
+                choice.reason.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Left", Some("Left"), true, None),
+            // Unavailable choices don't lend their target to the choices before them.
+            ("Look around", Some("Right"), true, None),
+            (
+                "<locked>Open the door</locked>",
+                Some("Door"),
+                false,
+                Some("if $keys > 0")
+            ),
+            // Choices without a target lead to the same passage as the next choice.
+            ("Wait", Some("Right"), true, None),
+            ("Right", Some("Right"), true, None),
+            ("Stay", None, true, None),
+        ]
+    );
+    assert_eq!(details[2].text, "Open the door");
+    assert_eq!(
+        serde_json::to_string(&details[2].attributes).unwrap(),
+        r#"[{"start":0,"end":13,"tag":"locked"}]"#
+    );
+}
+
+#[test]
+fn test_choice_details_invalid_condition() {
+    let story = Story::load("tests/data/choices_invalid").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, false).unwrap();
+    assert!(runner.next("").is_err());
+
+    // A condition that can't be evaluated is an error rather than a locked choice.
+    let err = build_choice_details(&runner, &[]).unwrap_err();
+    assert!(err.to_string().contains("Var 'missing' could not be found"));
+}
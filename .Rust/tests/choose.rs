@@ -0,0 +1,30 @@
+use kataru_ffi::{choose, get_choices, get_speech, init_runner, next};
+
+#[test]
+fn test_choose() {
+    let story_path = "tests/data/choices";
+    let bookmark_path = "tests/data/bookmark.yml";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+    let input = "";
+    next(input.as_ptr() as *const i8, input.len());
+    assert!(choose(0)
+        .as_str()
+        .contains("Called choose on a non-choice line."));
+
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(get_choices(), 5);
+    assert!(choose(5)
+        .as_str()
+        .contains("Invalid choice: index 5 is out of range for 5 choices."));
+
+    // Out of range indices leave the choices in place to choose again.
+    assert_eq!(get_choices(), 5);
+    assert_eq!(choose(2).as_str(), "");
+    assert_eq!(get_speech().as_str(), "Right.");
+}
@@ -1,5 +1,11 @@
 use kataru::*;
-use kataru_ffi::build_codegen_consts;
+use kataru_ffi::{
+    build_codegen, build_codegen_consts, build_codegen_handlers, build_codegen_split,
+    codegen_c_header, codegen_changed_files, codegen_consts, codegen_json, codegen_typescript,
+    init_runner, write_codegen, Backend, CBackend, CSharpBackend, CodegenOptions, FFIStr,
+    JsonBackend, TemplateBackend, TypeScriptBackend,
+};
+use std::fs;
 
 #[test]
 fn test_build_codegen_consts() {
@@ -36,18 +42,59 @@ namespace Kataru
     /// </summary>
     public static class Passages
     {
-        public const string None = "None",
-            EndDialogue = "EndDialogue",
-            Room1_RedSlimeTalk = "Room1:RedSlimeTalk",
-            Room1_RedSlimeTrigger = "Room1:RedSlimeTrigger",
-            Room2_AngryGreen = "Room2:AngryGreen",
-            Room2_BlueSlimeTalk = "Room2:BlueSlimeTalk",
-            Room2_GiveGoop = "Room2:GiveGoop",
-            Room2_GreenObserve = "Room2:GreenObserve",
-            Room2_NoGiveGoop = "Room2:NoGiveGoop",
-            Room2_Poster = "Room2:Poster",
-            Room2_ThanksGoop = "Room2:ThanksGoop",
-            Start = "Start";
+        public const string None = "None";
+
+        public const string EndDialogue = "EndDialogue";
+
+        /// <summary>
+        /// RedSlime: Test
+        /// </summary>
+        public const string Room1_RedSlimeTalk = "Room1:RedSlimeTalk";
+
+        /// <summary>
+        /// RedSlime: Test
+        /// </summary>
+        public const string Room1_RedSlimeTrigger = "Room1:RedSlimeTrigger";
+
+        /// <summary>
+        /// GreenSlime: &lt;size=200%&gt;&lt;b&gt;BLUE!!!!
+        /// </summary>
+        public const string Room2_AngryGreen = "Room2:AngryGreen";
+
+        /// <summary>
+        /// BlueSlime: Test
+        /// </summary>
+        public const string Room2_BlueSlimeTalk = "Room2:BlueSlimeTalk";
+
+        /// <summary>
+        /// Slime: Here.
+        /// </summary>
+        public const string Room2_GiveGoop = "Room2:GiveGoop";
+
+        /// <summary>
+        /// Slime: Eheh.. Hi..
+        /// </summary>
+        public const string Room2_GreenObserve = "Room2:GreenObserve";
+
+        /// <summary>
+        /// GreenSlime: No give goop
+        /// </summary>
+        public const string Room2_NoGiveGoop = "Room2:NoGiveGoop";
+
+        /// <summary>
+        /// Think: A poster of myself.
+        /// </summary>
+        public const string Room2_Poster = "Room2:Poster";
+
+        /// <summary>
+        /// Slime: Uh, yeah, I can try.
+        /// </summary>
+        public const string Room2_ThanksGoop = "Room2:ThanksGoop";
+
+        /// <summary>
+        /// Slime: Hey! Slime here.
+        /// </summary>
+        public const string Start = "Start";
 
         /// <summary>
         /// Get all passage names.
@@ -81,15 +128,26 @@ namespace Kataru
     /// </summary>
     public static partial class Characters
     {
-        public const string None = "None",
-            GlobalLight = "GlobalLight",
-            Room1_Party = "Room1:Party",
-            Room1_PartyLight = "Room1:PartyLight",
-            Room1_RedSlime = "Room1:RedSlime",
-            Room2_BlueSlime = "Room2:BlueSlime",
-            Room2_GreenSlime = "Room2:GreenSlime",
-            Slime = "Slime",
-            Think = "Think";
+        public const string None = "None";
+
+        public const string GlobalLight = "GlobalLight";
+
+        public const string Room1_Party = "Room1:Party";
+
+        public const string Room1_PartyLight = "Room1:PartyLight";
+
+        public const string Room1_RedSlime = "Room1:RedSlime";
+
+        public const string Room2_BlueSlime = "Room2:BlueSlime";
+
+        public const string Room2_GreenSlime = "Room2:GreenSlime";
+
+        /// <summary>
+        /// The player.
+        /// </summary>
+        public const string Slime = "Slime";
+
+        public const string Think = "Think";
 
         /// <summary>
         /// Get all character names.
@@ -114,6 +172,71 @@ namespace Kataru
             Think
         };
     }
+
+    /// <summary>
+    /// Autogenerated class containing all command strings.
+    /// Character commands are declared as `$character.Command`, and called as `Character.Command`.
+    /// </summary>
+    public static class Commands
+    {
+        public const string None = "None";
+
+        /// <summary>
+        /// Parameters: param = 0.
+        /// </summary>
+        public const string GlobalCommand = "GlobalCommand";
+
+        /// <summary>
+        /// Get all command names.
+        /// </summary>
+        public static string[] All() => all;
+
+        private static readonly string[] all = {
+            None,
+            GlobalCommand
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated class containing typed accessors for all state variables.
+    /// The type of each variable is inferred from its initial value.
+    /// </summary>
+    public static class State
+    {
+        /// <summary>
+        /// Autogenerated class containing all state variable names.
+        /// </summary>
+        public static class Keys
+        {
+            public const string None = "None",
+                var = "var";
+        }
+
+        /// <summary>
+        /// Initial value: false.
+        /// </summary>
+        public static bool var
+        {
+            get => Runner.GetState<bool>(Keys.var);
+            set => Runner.SetState(Keys.var, value);
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `GlobalCommand` command.
+    /// </summary>
+    public struct GlobalCommandParams
+    {
+        public double param;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly GlobalCommandParams Default = new GlobalCommandParams
+        {
+            param = 0
+        };
+    }
 }
 "###;
 
@@ -123,3 +246,402 @@ namespace Kataru
 
     assert_eq!(source, expected);
 }
+
+#[test]
+fn test_codegen_commands_and_state() {
+    let story = Story::load("tests/data/commands").unwrap();
+    let source = build_codegen_consts(&story).unwrap();
+    for expected in [
+        r#"public const string Character_SetAnimatorTrigger = "$character.SetAnimatorTrigger";"#,
+        "/// Parameters: door = \"Front\", sign = \"Keep out!\\n\\t\\\"Staff\\\" only\\u0007\".\n        /// </summary>\n        public const string Room_Open = \"Room:Open\";\n",
+        "public struct WaitParams\n    {\n        public double duration;\n        public bool skippable;\n",
+        "public struct Room_OpenParams\n    {\n        public string door;\n",
+        // Control characters in strings are escaped.
+        "            door = \"Front\",\n            sign = \"Keep out!\\n\\t\\\"Staff\\\" only\\u0007\"\n",
+        // Non-finite and large numbers are valid literals.
+        "            jitter = double.NaN,\n            limit = double.PositiveInfinity,\n            minimum = -1e300\n",
+        "public static bool muted\n        {\n            get => Runner.GetState<bool>(Keys.muted);\n",
+        "public static string Room_code\n",
+        r#"Room_code = "Room:code""#,
+    ] {
+        assert!(source.contains(expected), "Missing {:?} in:\n{}", expected, source);
+    }
+    // Commands without parameters don't get a struct.
+    assert!(!source.contains("FadeOutParams"));
+}
+
+#[test]
+fn test_codegen_identifiers() {
+    let story = Story::load("tests/data/codegen_names").unwrap();
+    let source = build_codegen_consts(&story).unwrap();
+    for expected in [
+        r#"public const string _2ndTry = "2ndTry";"#,
+        r#"public const string @class = "class";"#,
+        r#"public const string end_scene = "end-scene";"#,
+        r#"public const string My_Passage = "My Passage";"#,
+        "public bool @default;",
+    ] {
+        assert!(
+            source.contains(expected),
+            "Missing {:?} in:\n{}",
+            expected,
+            source
+        );
+    }
+}
+
+#[test]
+fn test_codegen_collisions() {
+    let mut story = Story::load("tests/data/codegen_collisions").unwrap();
+    let codegen_error = |story: &Story| format!("{:?}", build_codegen_consts(story).unwrap_err());
+
+    // `A:B_C` and `A_B:C` both map to `A_B_C`.
+    assert_eq!(
+        codegen_error(&story),
+        format!(
+            "{:?}",
+            error!("Passages 'A:B_C' and 'A_B:C' both generate the C# identifier 'A_B_C'.")
+        )
+    );
+
+    // Names can't shadow the members of the generated classes.
+    story.sections.remove("A_B");
+    let global = story.sections.get_mut(GLOBAL).unwrap();
+    global.passages.insert("None".to_string(), Passage::new());
+    assert_eq!(
+        codegen_error(&story),
+        format!(
+            "{:?}",
+            error!("Passage 'None' generates the reserved C# identifier 'None'.")
+        )
+    );
+}
+
+#[test]
+fn test_build_codegen_nested() {
+    let story = Story::load("tests/data/story").unwrap();
+    let source = build_codegen(
+        &story,
+        &CSharpBackend,
+        &CodegenOptions {
+            nested: true,
+            ..CodegenOptions::new()
+        },
+    )
+    .unwrap();
+    for expected in [
+        "    public static class Passages\n    {\n        public const string None = \"None\";\n",
+        "            /// RedSlime: Test\n            /// </summary>\n            public const string RedSlimeTalk = \"Room1:RedSlimeTalk\";\n",
+        "            Global.EndDialogue,\n            Global.Start,\n            Room1.RedSlimeTalk,\n",
+        "                { \"global\", Global.All() },\n                { \"Room1\", Room1.All() },\n",
+        "    public static partial class Characters\n",
+        "            public const string BlueSlime = \"Room2:BlueSlime\";\n",
+    ] {
+        assert!(source.contains(expected), "Missing {:?} in:\n{}", expected, source);
+    }
+    assert!(!source.contains("Room1_RedSlimeTalk"));
+    assert!(!source.contains("FilterByNamespace"));
+
+    // Members can't share the name of their namespace's class.
+    let mut story = Story::load("tests/data/story").unwrap();
+    let room = story.sections.get_mut("Room1").unwrap();
+    room.passages.insert("Room1".to_string(), Passage::new());
+    assert_eq!(
+        format!(
+            "{:?}",
+            build_codegen(
+                &story,
+                &CSharpBackend,
+                &CodegenOptions {
+                    nested: true,
+                    ..CodegenOptions::new()
+                }
+            )
+            .unwrap_err()
+        ),
+        format!(
+            "{:?}",
+            error!("Passage 'Room1:Room1' generates the reserved C# identifier 'Room1'.")
+        )
+    );
+}
+
+#[test]
+fn test_codegen_docs() {
+    let story = Story::load("tests/data/story").unwrap();
+    let options = CodegenOptions {
+        story_path: Some("tests/data/story".to_string()),
+        ..CodegenOptions::new()
+    };
+    let source = build_codegen(&story, &CSharpBackend, &options).unwrap();
+    for expected in [
+        "        /// <summary>\n        /// Declared in Rooms/Room1.yml.\n        /// RedSlime: Test\n        /// </summary>\n        public const string Room1_RedSlimeTalk = \"Room1:RedSlimeTalk\";\n",
+        "        /// Declared in global.yml.\n        /// The player.\n        /// </summary>\n        public const string Slime = \"Slime\";\n",
+        "        /// Declared in Rooms/Room2.yml.\n        /// GreenSlime: &lt;size=200%&gt;&lt;b&gt;BLUE!!!!\n",
+        "        /// Declared in global.yml.\n        /// Parameters: param = 0.\n        /// </summary>\n        public const string GlobalCommand = \"GlobalCommand\";\n",
+        "        /// Initial value: false.\n        /// </summary>\n        public static bool var\n",
+    ] {
+        assert!(source.contains(expected), "Missing {:?} in:\n{}", expected, source);
+    }
+
+    // The default template renders the same doc comments.
+    assert_eq!(
+        build_codegen(&story, &TemplateBackend::default(), &options).unwrap(),
+        source
+    );
+}
+
+#[test]
+fn test_codegen_docs_without_sources() {
+    // Compiled stories have no sources to locate, which leaves out where symbols are declared.
+    let story = Story::load("tests/data/story").unwrap();
+    let target = std::env::temp_dir().join("kataru_ffi_codegen_docs.bin");
+    story.save_mp(&target).unwrap();
+    let options = CodegenOptions {
+        story_path: Some(target.to_string_lossy().into_owned()),
+        ..CodegenOptions::new()
+    };
+    assert_eq!(
+        build_codegen(&story, &CSharpBackend, &options).unwrap(),
+        build_codegen_consts(&story).unwrap()
+    );
+}
+
+#[test]
+fn test_codegen_docs_raw_configs() {
+    let mut story = Story::load("tests/data/characters").unwrap();
+    let mut params = Params::new();
+    params.insert("text".to_string(), Value::String("Say \"hi\"".to_string()));
+    story
+        .sections
+        .get_mut(GLOBAL)
+        .unwrap()
+        .config
+        .commands
+        .insert("Say".to_string(), Some(params));
+    let options = CodegenOptions {
+        story_path: Some("tests/data/characters".to_string()),
+        ..CodegenOptions::new()
+    };
+    let source = build_codegen(&story, &CSharpBackend, &options).unwrap();
+    for expected in [
+        // Character docs list every field of the raw config, not just the description.
+        "        /// Declared in global.yml.\n        /// The protagonist.\n        /// Fields: name = \"Alice Liddell\", portrait = \"alice_neutral\", portraits = {\"happy\":\"alice_happy\",\"sad\":\"alice_sad\"}.\n",
+        "        /// Fields: name = \"Alice in the Room\".\n",
+        // Strings are quoted the same way as in the generated code.
+        "        /// Parameters: text = \"Say \\\"hi\\\"\".\n",
+    ] {
+        assert!(source.contains(expected), "Missing {:?} in:\n{}", expected, source);
+    }
+}
+
+/// Compares `source` against `tests/data/codegen_golden/{file}`.
+/// Set `KATARU_UPDATE_GOLDEN` to rewrite the golden file instead.
+fn assert_golden(source: &str, file: &str) {
+    let path = format!("tests/data/codegen_golden/{}", file);
+    if std::env::var_os("KATARU_UPDATE_GOLDEN").is_some() {
+        fs::write(&path, source).unwrap();
+    }
+    assert_eq!(source, fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn test_codegen_backends() {
+    let story = Story::load("tests/data/commands").unwrap();
+    let backends: [(&dyn Backend, &str); 4] = [
+        (&CSharpBackend, "Constants.cs"),
+        (&CBackend, "constants.h"),
+        (&TypeScriptBackend, "constants.ts"),
+        (&JsonBackend, "symbols.json"),
+    ];
+    for (backend, file) in backends {
+        let source = build_codegen(&story, backend, &CodegenOptions::new()).unwrap();
+        assert_golden(&source, file);
+    }
+}
+
+#[test]
+fn test_codegen_extra_backends() {
+    let story_path = "tests/data/commands";
+    let bookmark_path = "tests/data/bookmark.yml";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+
+    // The constants are always C#, and other languages are written to their own paths.
+    let dir = std::env::temp_dir().join("kataru_ffi_codegen_backends");
+    fs::create_dir_all(&dir).unwrap();
+    let story = Story::load(story_path).unwrap();
+    let options = CodegenOptions {
+        story_path: Some(story_path.to_string()),
+        ..CodegenOptions::new()
+    };
+    type Codegen = extern "C" fn(*const i8, usize) -> FFIStr;
+    let outputs: [(Codegen, &dyn Backend, &str); 4] = [
+        (codegen_consts, &CSharpBackend, "Constants.Generated.cs"),
+        (codegen_c_header, &CBackend, "constants.h"),
+        (codegen_typescript, &TypeScriptBackend, "constants.ts"),
+        (codegen_json, &JsonBackend, "symbols.json"),
+    ];
+    let mut paths = Vec::new();
+    for (codegen, backend, file) in outputs {
+        let path = dir.join(file);
+        let _ = fs::remove_file(&path);
+        let path = path.to_str().unwrap().to_string();
+        assert_eq!(codegen(path.as_ptr() as *const i8, path.len()).as_str(), "");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            build_codegen(&story, backend, &options).unwrap()
+        );
+        paths.push(path);
+    }
+    let changed: Vec<String> = serde_json::from_str(codegen_changed_files().as_str()).unwrap();
+    assert_eq!(changed, paths);
+}
+
+#[test]
+fn test_codegen_handlers() {
+    let story = Story::load("tests/data/commands").unwrap();
+    assert_golden(
+        &build_codegen_handlers(&story, &CodegenOptions::new()).unwrap(),
+        "Handlers.cs",
+    );
+}
+
+#[test]
+fn test_codegen_template() {
+    // The default template reproduces the C# backend's flat layout.
+    for path in ["tests/data/story", "tests/data/commands"] {
+        let story = Story::load(path).unwrap();
+        assert_eq!(
+            build_codegen(&story, &TemplateBackend::default(), &CodegenOptions::new()).unwrap(),
+            build_codegen_consts(&story).unwrap()
+        );
+    }
+
+    let story = Story::load("tests/data/commands").unwrap();
+    let template = TemplateBackend::new(
+        "namespace Studio.Dialogue {\n\
+         {% for passage in passages %}readonly static string {{ passage.varname }} = {{ passage.literal }};\n{% endfor %}\
+         {% for command in commands %}// {{ command.name }}({% for param in command.params %}{{ param.type }} {{ param.varname }}{% endfor %})\n{% endfor %}\
+         {% for variable in variables %}// {{ variable.qualified }}: {{ variable.type }} = {{ variable.value }}\n{% endfor %}\
+         }\n",
+    );
+    assert_eq!(
+        build_codegen(&story, &template, &CodegenOptions::new()).unwrap(),
+        r#"namespace Studio.Dialogue {
+readonly static string Room_Enter = "Room:Enter";
+readonly static string Start = "Start";
+// $character.SetAnimatorTrigger(string trigger)
+// FadeOut()
+// Open(string doorstring sign)
+// Wait(double framesdouble jitterdouble limitdouble minimum)
+// Wait(double durationbool skippable)
+// Room:code: string = "1234"
+// muted: bool = false
+}
+"#
+    );
+
+    let error = build_codegen(
+        &story,
+        &TemplateBackend::new("{% for %}"),
+        &CodegenOptions::new(),
+    )
+    .unwrap_err();
+    assert!(format!("{:?}", error).contains("Error rendering codegen template"));
+}
+
+#[test]
+fn test_codegen_template_layout_options() {
+    // Templates decide their own layout, so nesting and splitting are rejected rather than ignored.
+    let story = Story::load("tests/data/commands").unwrap();
+    let nested = CodegenOptions {
+        nested: true,
+        ..CodegenOptions::new()
+    };
+    let error = build_codegen(&story, &TemplateBackend::default(), &nested).unwrap_err();
+    assert!(format!("{:?}", error)
+        .contains("Nested codegen output isn't supported with a codegen template."));
+
+    let split = CodegenOptions {
+        split: true,
+        ..CodegenOptions::new()
+    };
+    let error = build_codegen_split(&story, &TemplateBackend::default(), &split).unwrap_err();
+    assert!(format!("{:?}", error)
+        .contains("Split codegen output isn't supported with a codegen template."));
+}
+
+#[test]
+fn test_codegen_split() {
+    let story = Story::load("tests/data/commands").unwrap();
+    for (nested, prefix) in [(false, "Split"), (true, "SplitNested")] {
+        let options = CodegenOptions {
+            nested,
+            split: true,
+            ..CodegenOptions::new()
+        };
+        let split = build_codegen_split(&story, &CSharpBackend, &options).unwrap();
+        assert_golden(&split.index, &format!("{}.cs", prefix));
+        let suffixes: Vec<&str> = split
+            .namespaces
+            .iter()
+            .map(|(suffix, _)| suffix.as_str())
+            .collect();
+        assert_eq!(suffixes, ["Global", "Room"]);
+        for (suffix, source) in &split.namespaces {
+            assert_golden(source, &format!("{}.{}.cs", prefix, suffix));
+        }
+    }
+    assert!(build_codegen_split(&story, &JsonBackend, &CodegenOptions::new()).is_err());
+
+    // Only changed files are written, and files of removed namespaces are cleaned up.
+    let dir = std::env::temp_dir().join("kataru_ffi_codegen_split");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Constants.cs").to_string_lossy().into_owned();
+    let file = |name: &str| dir.join(name).to_string_lossy().into_owned();
+    let options = CodegenOptions {
+        split: true,
+        ..CodegenOptions::new()
+    };
+    assert_eq!(
+        write_codegen(&story, &CSharpBackend, &options, &path).unwrap(),
+        [
+            path.clone(),
+            file("Constants.Global.cs"),
+            file("Constants.Room.cs")
+        ]
+    );
+    assert!(write_codegen(&story, &CSharpBackend, &options, &path)
+        .unwrap()
+        .is_empty());
+
+    fs::rename(file("Constants.Room.cs"), file("Constants.Removed.cs")).unwrap();
+    fs::write(file("Constants.Handwritten.cs"), "// Not generated.").unwrap();
+    assert_eq!(
+        write_codegen(&story, &CSharpBackend, &options, &path).unwrap(),
+        [file("Constants.Room.cs"), file("Constants.Removed.cs")]
+    );
+    assert!(fs::metadata(file("Constants.Handwritten.cs")).is_ok());
+
+    // Turning splitting off removes the per-namespace files.
+    assert_eq!(
+        write_codegen(&story, &CSharpBackend, &CodegenOptions::new(), &path).unwrap(),
+        [
+            path.clone(),
+            file("Constants.Global.cs"),
+            file("Constants.Room.cs")
+        ]
+    );
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        build_codegen_consts(&story).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,104 @@
+use kataru::*;
+use kataru_ffi::{build_command_list, build_handler_report, CommandName, HandlerReport};
+
+#[test]
+fn test_command_list() {
+    let story = Story::load("tests/data/commands").unwrap();
+    assert_eq!(
+        build_command_list(&story, GLOBAL).unwrap(),
+        concat!(
+            r#"[{"name":"$character.SetAnimatorTrigger","namespace":"global","base_name":"SetAnimatorTrigger","character":true,"params":[{"name":"trigger","default":"","type":"string"}]},"#,
+            r#"{"name":"FadeOut","namespace":"global","base_name":"FadeOut","character":false,"params":[]},"#,
+            r#"{"name":"Wait","namespace":"global","base_name":"Wait","character":false,"params":[{"name":"duration","default":1.0,"type":"number"},{"name":"skippable","default":true,"type":"bool"}]}]"#
+        )
+    );
+
+    // Local commands shadow global ones with the same name.
+    let commands: serde_json::Value =
+        serde_json::from_str(&build_command_list(&story, "Room").unwrap()).unwrap();
+    let names: Vec<&str> = commands
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|command| command["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "Room:Open",
+            "Room:Wait",
+            "$character.SetAnimatorTrigger",
+            "FadeOut"
+        ]
+    );
+    assert!(build_command_list(&story, "Room3").is_err());
+}
+
+#[test]
+fn test_command_names() {
+    let story = Story::load("tests/data/commands").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+    let mut next_name = |passage: Option<&str>| {
+        let line = match passage {
+            Some(passage) => runner.run(passage.to_string()),
+            None => runner.next(""),
+        };
+        match line.unwrap() {
+            Line::Command(command) => command.name,
+            line => panic!("Expected command, got {:?}.", line),
+        }
+    };
+
+    let name = next_name(Some("Room:Enter"));
+    assert_eq!(
+        CommandName::parse(&name),
+        CommandName {
+            character: "",
+            base_name: "Open",
+            qualified_name: "Room:Open"
+        }
+    );
+    let name = next_name(None);
+    assert_eq!(
+        CommandName::parse(&name),
+        CommandName {
+            character: "May",
+            base_name: "SetAnimatorTrigger",
+            qualified_name: "May.SetAnimatorTrigger"
+        }
+    );
+    let name = next_name(None);
+    assert_eq!(
+        CommandName::parse(&name),
+        CommandName {
+            character: "Room:Guard",
+            base_name: "SetAnimatorTrigger",
+            qualified_name: "Room:Guard.SetAnimatorTrigger"
+        }
+    );
+}
+
+#[test]
+fn test_handler_report() {
+    let story = Story::load("tests/data/commands").unwrap();
+    let report = build_handler_report(
+        &story,
+        &[
+            "Wait",
+            "Room:Open",
+            "May.SetAnimatorTrigger",
+            "Room:Guard.Wave",
+            "Fade",
+        ],
+        &["May"],
+    );
+    assert_eq!(
+        report,
+        HandlerReport {
+            unhandled_commands: vec!["FadeOut".to_string(), "Room:Wait".to_string()],
+            undeclared_handlers: vec!["Fade".to_string(), "Room:Guard.Wave".to_string()],
+            unhandled_characters: vec!["Room:Guard".to_string()],
+        }
+    );
+}
@@ -0,0 +1,236 @@
+/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+
+namespace Kataru
+{
+    /// <summary>
+    /// Autogenerated class containing all namespace strings.
+    /// </summary>
+    public static class Namespaces
+    {
+        public const string Global = "global",
+            Room = "Room";
+
+        /// <summary>
+        /// Get all namespace names.
+        /// </summary>
+        public static string[] All() => all;
+
+        private static readonly string[] all = {
+            Global,
+            Room
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated class containing all passage strings.
+    /// </summary>
+    public static class Passages
+    {
+        public const string None = "None";
+
+        public const string Room_Enter = "Room:Enter";
+
+        public const string Start = "Start";
+
+        /// <summary>
+        /// Get all passage names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all passage names in a given namespace.
+        /// This requires a linear search over all passage names, so don't do this at runtime.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) => NamespaceUtils.FilterByNamespace(all, @namespace);
+
+        private static readonly string[] all = {
+            None,
+            Room_Enter,
+            Start
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated class containing all character strings.
+    /// </summary>
+    public static partial class Characters
+    {
+        public const string None = "None";
+
+        public const string May = "May";
+
+        public const string Room_Guard = "Room:Guard";
+
+        /// <summary>
+        /// Get all character names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all character names in a given namespace.
+        /// This requires a linear search over all passage names, so don't do this at runtime.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) => NamespaceUtils.FilterByNamespace(all, @namespace);
+
+        private static readonly string[] all = {
+            None,
+            May,
+            Room_Guard
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated class containing all command strings.
+    /// Character commands are declared as `$character.Command`, and called as `Character.Command`.
+    /// </summary>
+    public static class Commands
+    {
+        public const string None = "None";
+
+        /// <summary>
+        /// Parameters: trigger = "".
+        /// </summary>
+        public const string Character_SetAnimatorTrigger = "$character.SetAnimatorTrigger";
+
+        public const string FadeOut = "FadeOut";
+
+        /// <summary>
+        /// Parameters: door = "Front", sign = "Keep out!\n\t\"Staff\" only\u0007".
+        /// </summary>
+        public const string Room_Open = "Room:Open";
+
+        /// <summary>
+        /// Parameters: frames = 30, jitter = .nan, limit = .inf, minimum = -1e300.
+        /// </summary>
+        public const string Room_Wait = "Room:Wait";
+
+        /// <summary>
+        /// Parameters: duration = 1, skippable = true.
+        /// </summary>
+        public const string Wait = "Wait";
+
+        /// <summary>
+        /// Get all command names.
+        /// </summary>
+        public static string[] All() => all;
+
+        private static readonly string[] all = {
+            None,
+            Character_SetAnimatorTrigger,
+            FadeOut,
+            Room_Open,
+            Room_Wait,
+            Wait
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated class containing typed accessors for all state variables.
+    /// The type of each variable is inferred from its initial value.
+    /// </summary>
+    public static class State
+    {
+        /// <summary>
+        /// Autogenerated class containing all state variable names.
+        /// </summary>
+        public static class Keys
+        {
+            public const string None = "None",
+                Room_code = "Room:code",
+                muted = "muted";
+        }
+
+        /// <summary>
+        /// Initial value: "1234".
+        /// </summary>
+        public static string Room_code
+        {
+            get => Runner.GetState<string>(Keys.Room_code);
+            set => Runner.SetState(Keys.Room_code, value);
+        }
+
+        /// <summary>
+        /// Initial value: false.
+        /// </summary>
+        public static bool muted
+        {
+            get => Runner.GetState<bool>(Keys.muted);
+            set => Runner.SetState(Keys.muted, value);
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `$character.SetAnimatorTrigger` command.
+    /// </summary>
+    public struct Character_SetAnimatorTriggerParams
+    {
+        public string trigger;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly Character_SetAnimatorTriggerParams Default = new Character_SetAnimatorTriggerParams
+        {
+            trigger = ""
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `Room:Open` command.
+    /// </summary>
+    public struct Room_OpenParams
+    {
+        public string door;
+        public string sign;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly Room_OpenParams Default = new Room_OpenParams
+        {
+            door = "Front",
+            sign = "Keep out!\n\t\"Staff\" only\u0007"
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `Room:Wait` command.
+    /// </summary>
+    public struct Room_WaitParams
+    {
+        public double frames;
+        public double jitter;
+        public double limit;
+        public double minimum;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly Room_WaitParams Default = new Room_WaitParams
+        {
+            frames = 30,
+            jitter = double.NaN,
+            limit = double.PositiveInfinity,
+            minimum = -1e300
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `Wait` command.
+    /// </summary>
+    public struct WaitParams
+    {
+        public double duration;
+        public bool skippable;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly WaitParams Default = new WaitParams
+        {
+            duration = 1,
+            skippable = true
+        };
+    }
+}
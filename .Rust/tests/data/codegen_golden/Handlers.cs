@@ -0,0 +1,65 @@
+/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+
+namespace Kataru
+{
+    /// <summary>
+    /// Autogenerated base class with a handler for each command.
+    /// Inherit from it and implement every method, so that missing handlers are compile errors.
+    /// </summary>
+    public abstract class CommandHandlerBase : Handler
+    {
+        [CommandHandler("FadeOut")]
+        protected abstract void FadeOut();
+
+        /// <summary>
+        /// Parameters: door = "Front", sign = "Keep out!\n\t\"Staff\" only\u0007".
+        /// </summary>
+        [CommandHandler("Room:Open")]
+        protected abstract void Room_Open(Room_OpenParams @params);
+
+        /// <summary>
+        /// Parameters: frames = 30, jitter = .nan, limit = .inf, minimum = -1e300.
+        /// </summary>
+        [CommandHandler("Room:Wait")]
+        protected abstract void Room_Wait(Room_WaitParams @params);
+
+        /// <summary>
+        /// Parameters: duration = 1, skippable = true.
+        /// </summary>
+        [CommandHandler("Wait")]
+        protected abstract void Wait(WaitParams @params);
+    }
+
+    /// <summary>
+    /// Autogenerated base class with a handler for a character's dialogue and each character command.
+    /// Inherit from a character's generated subclass, or from this class and override Name.
+    /// </summary>
+    public abstract class CharacterHandlerBase : Handler
+    {
+        [CharacterHandler]
+        protected abstract void OnDialogue(Dialogue dialogue);
+
+        /// <summary>
+        /// Parameters: trigger = "".
+        /// </summary>
+        [CommandHandler("SetAnimatorTrigger", character: true)]
+        protected abstract void SetAnimatorTrigger(Character_SetAnimatorTriggerParams @params);
+    }
+
+    /// <summary>
+    /// Autogenerated base class for handling the `May` character.
+    /// </summary>
+    public abstract class MayHandlerBase : CharacterHandlerBase
+    {
+        public sealed override string Name => "May";
+    }
+
+    /// <summary>
+    /// Autogenerated base class for handling the `Room:Guard` character.
+    /// </summary>
+    public abstract class Room_GuardHandlerBase : CharacterHandlerBase
+    {
+        public sealed override string Name => "Room:Guard";
+    }
+}
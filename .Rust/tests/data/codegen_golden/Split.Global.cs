@@ -0,0 +1,96 @@
+/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+/// It declares the `global` namespace's part of the split Kataru constants.
+
+namespace Kataru
+{
+    public static partial class Passages
+    {
+        public const string Start = "Start";
+
+        private static readonly string[] all_Global = {
+            Start
+        };
+    }
+
+    public static partial class Characters
+    {
+        public const string May = "May";
+
+        private static readonly string[] all_Global = {
+            May
+        };
+    }
+
+    public static partial class Commands
+    {
+        /// <summary>
+        /// Parameters: trigger = "".
+        /// </summary>
+        public const string Character_SetAnimatorTrigger = "$character.SetAnimatorTrigger";
+
+        public const string FadeOut = "FadeOut";
+
+        /// <summary>
+        /// Parameters: duration = 1, skippable = true.
+        /// </summary>
+        public const string Wait = "Wait";
+
+        private static readonly string[] all_Global = {
+            Character_SetAnimatorTrigger,
+            FadeOut,
+            Wait
+        };
+    }
+
+    public static partial class State
+    {
+        public static partial class Keys
+        {
+            public const string muted = "muted";
+        }
+
+        /// <summary>
+        /// Initial value: false.
+        /// </summary>
+        public static bool muted
+        {
+            get => Runner.GetState<bool>(Keys.muted);
+            set => Runner.SetState(Keys.muted, value);
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `$character.SetAnimatorTrigger` command.
+    /// </summary>
+    public struct Character_SetAnimatorTriggerParams
+    {
+        public string trigger;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly Character_SetAnimatorTriggerParams Default = new Character_SetAnimatorTriggerParams
+        {
+            trigger = ""
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `Wait` command.
+    /// </summary>
+    public struct WaitParams
+    {
+        public double duration;
+        public bool skippable;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly WaitParams Default = new WaitParams
+        {
+            duration = 1,
+            skippable = true
+        };
+    }
+}
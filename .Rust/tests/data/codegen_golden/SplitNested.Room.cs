@@ -0,0 +1,121 @@
+/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+/// It declares the `Room` namespace's part of the split Kataru constants.
+
+namespace Kataru
+{
+    public static partial class Passages
+    {
+        /// <summary>
+        /// Autogenerated class containing all passage strings in the `Room` namespace.
+        /// </summary>
+        public static class Room
+        {
+            public const string Enter = "Room:Enter";
+
+            /// <summary>
+            /// Get all passage names in this namespace.
+            /// </summary>
+            public static string[] All() => all;
+
+            private static readonly string[] all = {
+                Enter
+            };
+        }
+    }
+
+    public static partial class Characters
+    {
+        /// <summary>
+        /// Autogenerated class containing all character strings in the `Room` namespace.
+        /// </summary>
+        public static class Room
+        {
+            public const string Guard = "Room:Guard";
+
+            /// <summary>
+            /// Get all character names in this namespace.
+            /// </summary>
+            public static string[] All() => all;
+
+            private static readonly string[] all = {
+                Guard
+            };
+        }
+    }
+
+    public static partial class Commands
+    {
+        /// <summary>
+        /// Parameters: door = "Front", sign = "Keep out!\n\t\"Staff\" only\u0007".
+        /// </summary>
+        public const string Room_Open = "Room:Open";
+
+        /// <summary>
+        /// Parameters: frames = 30, jitter = .nan, limit = .inf, minimum = -1e300.
+        /// </summary>
+        public const string Room_Wait = "Room:Wait";
+
+        private static readonly string[] all_Room = {
+            Room_Open,
+            Room_Wait
+        };
+    }
+
+    public static partial class State
+    {
+        public static partial class Keys
+        {
+            public const string Room_code = "Room:code";
+        }
+
+        /// <summary>
+        /// Initial value: "1234".
+        /// </summary>
+        public static string Room_code
+        {
+            get => Runner.GetState<string>(Keys.Room_code);
+            set => Runner.SetState(Keys.Room_code, value);
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `Room:Open` command.
+    /// </summary>
+    public struct Room_OpenParams
+    {
+        public string door;
+        public string sign;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly Room_OpenParams Default = new Room_OpenParams
+        {
+            door = "Front",
+            sign = "Keep out!\n\t\"Staff\" only\u0007"
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated parameters of the `Room:Wait` command.
+    /// </summary>
+    public struct Room_WaitParams
+    {
+        public double frames;
+        public double jitter;
+        public double limit;
+        public double minimum;
+
+        /// <summary>
+        /// The parameters with their declared defaults.
+        /// </summary>
+        public static readonly Room_WaitParams Default = new Room_WaitParams
+        {
+            frames = 30,
+            jitter = double.NaN,
+            limit = double.PositiveInfinity,
+            minimum = -1e300
+        };
+    }
+}
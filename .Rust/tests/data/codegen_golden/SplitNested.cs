@@ -0,0 +1,155 @@
+/// DO NOT EDIT.
+/// This file was autogenerated by Kataru based on your scripts.
+/// Each namespace is declared in its own file next to this one.
+
+namespace Kataru
+{
+    /// <summary>
+    /// Autogenerated class containing all namespace strings.
+    /// </summary>
+    public static class Namespaces
+    {
+        public const string Global = "global",
+            Room = "Room";
+
+        /// <summary>
+        /// Get all namespace names.
+        /// </summary>
+        public static string[] All() => all;
+
+        private static readonly string[] all = {
+            Global,
+            Room
+        };
+    }
+
+    /// <summary>
+    /// Autogenerated class containing all passage strings.
+    /// The passages of each namespace are declared in that namespace's file.
+    /// </summary>
+    public static partial class Passages
+    {
+        public const string None = "None";
+
+        /// <summary>
+        /// Get all passage names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all passage names in a given namespace, which are precomputed.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) =>
+            byNamespace.TryGetValue(@namespace, out var names) ? names : new string[0];
+
+        private static readonly string[] all;
+
+        private static readonly System.Collections.Generic.Dictionary<string, string[]> byNamespace;
+
+        // Built in the static constructor, which runs after the initializers in every namespace's file.
+        static Passages()
+        {
+            byNamespace = new System.Collections.Generic.Dictionary<string, string[]>
+            {
+                { "global", Global.All() },
+                { "Room", Room.All() }
+            };
+            var names = new System.Collections.Generic.List<string> { None };
+            names.AddRange(Global.All());
+            names.AddRange(Room.All());
+            all = names.ToArray();
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated class containing all character strings.
+    /// The characters of each namespace are declared in that namespace's file.
+    /// </summary>
+    public static partial class Characters
+    {
+        public const string None = "None";
+
+        /// <summary>
+        /// Get all character names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all character names in a given namespace, which are precomputed.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) =>
+            byNamespace.TryGetValue(@namespace, out var names) ? names : new string[0];
+
+        private static readonly string[] all;
+
+        private static readonly System.Collections.Generic.Dictionary<string, string[]> byNamespace;
+
+        // Built in the static constructor, which runs after the initializers in every namespace's file.
+        static Characters()
+        {
+            byNamespace = new System.Collections.Generic.Dictionary<string, string[]>
+            {
+                { "global", Global.All() },
+                { "Room", Room.All() }
+            };
+            var names = new System.Collections.Generic.List<string> { None };
+            names.AddRange(Global.All());
+            names.AddRange(Room.All());
+            all = names.ToArray();
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated class containing all command strings.
+    /// The commands of each namespace are declared in that namespace's file.
+    /// </summary>
+    public static partial class Commands
+    {
+        public const string None = "None";
+
+        /// <summary>
+        /// Get all command names.
+        /// </summary>
+        public static string[] All() => all;
+
+        /// <summary>
+        /// Get all command names in a given namespace, which are precomputed.
+        /// </summary>
+        public static string[] InNamespace(string @namespace) =>
+            byNamespace.TryGetValue(@namespace, out var names) ? names : new string[0];
+
+        private static readonly string[] all;
+
+        private static readonly System.Collections.Generic.Dictionary<string, string[]> byNamespace;
+
+        // Built in the static constructor, which runs after the initializers in every namespace's file.
+        static Commands()
+        {
+            byNamespace = new System.Collections.Generic.Dictionary<string, string[]>
+            {
+                { "global", all_Global },
+                { "Room", all_Room }
+            };
+            var names = new System.Collections.Generic.List<string> { None };
+            names.AddRange(all_Global);
+            names.AddRange(all_Room);
+            all = names.ToArray();
+        }
+    }
+
+    /// <summary>
+    /// Autogenerated class containing typed accessors for all state variables.
+    /// The type of each variable is inferred from its initial value.
+    /// The variables of each namespace are declared in that namespace's file.
+    /// </summary>
+    public static partial class State
+    {
+        /// <summary>
+        /// Autogenerated class containing all state variable names.
+        /// </summary>
+        public static partial class Keys
+        {
+            public const string None = "None";
+        }
+    }
+}
@@ -0,0 +1,65 @@
+/* DO NOT EDIT.
+ * This file was autogenerated by Kataru based on your scripts.
+ */
+#ifndef KATARU_CONSTANTS_H
+#define KATARU_CONSTANTS_H
+
+#include <math.h>
+#include <stdbool.h>
+
+/* Namespaces */
+#define KATARU_NAMESPACE_ROOM "Room"
+
+/* Passages */
+#define KATARU_PASSAGE_ROOM_ENTER "Room:Enter"
+#define KATARU_PASSAGE_START "Start"
+
+/* Characters */
+#define KATARU_CHARACTER_MAY "May"
+#define KATARU_CHARACTER_ROOM_GUARD "Room:Guard"
+
+/* Commands */
+#define KATARU_COMMAND_CHARACTER_SETANIMATORTRIGGER "$character.SetAnimatorTrigger"
+#define KATARU_COMMAND_FADEOUT "FadeOut"
+#define KATARU_COMMAND_ROOM_OPEN "Room:Open"
+#define KATARU_COMMAND_ROOM_WAIT "Room:Wait"
+#define KATARU_COMMAND_WAIT "Wait"
+
+/* State variables */
+#define KATARU_STATE_ROOM_CODE "Room:code"
+#define KATARU_STATE_MUTED "muted"
+
+/* Parameters of the `$character.SetAnimatorTrigger` command. */
+typedef struct {
+    const char *trigger;
+} kataru_character_setanimatortrigger_params;
+
+#define KATARU_CHARACTER_SETANIMATORTRIGGER_PARAMS_DEFAULT { .trigger = "" }
+
+/* Parameters of the `Room:Open` command. */
+typedef struct {
+    const char *door;
+    const char *sign;
+} kataru_room_open_params;
+
+#define KATARU_ROOM_OPEN_PARAMS_DEFAULT { .door = "Front", .sign = "Keep out!\n\t\"Staff\" only\007" }
+
+/* Parameters of the `Room:Wait` command. */
+typedef struct {
+    double frames;
+    double jitter;
+    double limit;
+    double minimum;
+} kataru_room_wait_params;
+
+#define KATARU_ROOM_WAIT_PARAMS_DEFAULT { .frames = 30, .jitter = NAN, .limit = INFINITY, .minimum = -1e300 }
+
+/* Parameters of the `Wait` command. */
+typedef struct {
+    double duration;
+    bool skippable;
+} kataru_wait_params;
+
+#define KATARU_WAIT_PARAMS_DEFAULT { .duration = 1, .skippable = true }
+
+#endif /* KATARU_CONSTANTS_H */
@@ -0,0 +1,82 @@
+// DO NOT EDIT.
+// This file was autogenerated by Kataru based on your scripts.
+
+export const Namespaces = {
+  Room: "Room",
+} as const;
+export type Namespace = (typeof Namespaces)[keyof typeof Namespaces];
+
+export const Passages = {
+  Room_Enter: "Room:Enter",
+  Start: "Start",
+} as const;
+export type Passage = (typeof Passages)[keyof typeof Passages];
+
+export const Characters = {
+  May: "May",
+  Room_Guard: "Room:Guard",
+} as const;
+export type Character = (typeof Characters)[keyof typeof Characters];
+
+export const Commands = {
+  Character_SetAnimatorTrigger: "$character.SetAnimatorTrigger",
+  FadeOut: "FadeOut",
+  Room_Open: "Room:Open",
+  Room_Wait: "Room:Wait",
+  Wait: "Wait",
+} as const;
+export type Command = (typeof Commands)[keyof typeof Commands];
+
+export const State = {
+  Room_code: "Room:code",
+  muted: "muted",
+} as const;
+export type StateVariable = (typeof State)[keyof typeof State];
+
+/** The type of each state variable. */
+export interface StateTypes {
+  "Room:code": string;
+  muted: boolean;
+}
+
+/** Parameters of the `$character.SetAnimatorTrigger` command. */
+export interface Character_SetAnimatorTriggerParams {
+  trigger: string;
+}
+export const Character_SetAnimatorTriggerDefaults: Character_SetAnimatorTriggerParams = {
+  trigger: "",
+};
+
+/** Parameters of the `Room:Open` command. */
+export interface Room_OpenParams {
+  door: string;
+  sign: string;
+}
+export const Room_OpenDefaults: Room_OpenParams = {
+  door: "Front",
+  sign: "Keep out!\n\t\"Staff\" only\u0007",
+};
+
+/** Parameters of the `Room:Wait` command. */
+export interface Room_WaitParams {
+  frames: number;
+  jitter: number;
+  limit: number;
+  minimum: number;
+}
+export const Room_WaitDefaults: Room_WaitParams = {
+  frames: 30,
+  jitter: NaN,
+  limit: Infinity,
+  minimum: -1e300,
+};
+
+/** Parameters of the `Wait` command. */
+export interface WaitParams {
+  duration: number;
+  skippable: boolean;
+}
+export const WaitDefaults: WaitParams = {
+  duration: 1,
+  skippable: true,
+};
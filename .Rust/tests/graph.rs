@@ -0,0 +1,108 @@
+use kataru::*;
+use kataru_ffi::{
+    build_story_graph, build_story_graph_dot, build_story_graph_file, EdgeKind, GraphEdge,
+};
+
+fn edge(
+    from: &str,
+    to: &str,
+    kind: EdgeKind,
+    label: Option<&str>,
+    conditions: &[&str],
+) -> GraphEdge {
+    GraphEdge {
+        from: from.to_string(),
+        to: to.to_string(),
+        kind,
+        label: label.map(str::to_string),
+        conditions: conditions
+            .iter()
+            .map(|condition| condition.to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn test_story_graph() {
+    let story = Story::load("tests/data/graph").unwrap();
+    let graph = build_story_graph(&story);
+
+    let ids: Vec<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["End", "Shop", "Start", "Sword", "Room:Cellar", "Room:Enter"]
+    );
+    // `Room:Cellar` is called but never declared.
+    let missing: Vec<&str> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.missing)
+        .map(|node| node.id.as_str())
+        .collect();
+    assert_eq!(missing, ["Room:Cellar"]);
+
+    assert_eq!(
+        graph.edges,
+        [
+            // Only the last branch returns right after its call.
+            edge("Shop", "Sword", EdgeKind::Call, None, &["if $gold > 0"]),
+            edge("Shop", "End", EdgeKind::Goto, None, &["not ($gold > 0)"]),
+            edge("Start", "Room:Enter", EdgeKind::Call, None, &[]),
+            // `Browse` has no target, so it falls through to the next available choice's.
+            edge(
+                "Start",
+                "Sword",
+                EdgeKind::Choice,
+                Some("Browse"),
+                &["if $gold > 0"]
+            ),
+            edge(
+                "Start",
+                "Shop",
+                EdgeKind::Choice,
+                Some("Browse"),
+                &["not ($gold > 0)"]
+            ),
+            edge(
+                "Start",
+                "Sword",
+                EdgeKind::Choice,
+                Some("Buy a sword"),
+                &["if $gold > 0"]
+            ),
+            edge("Start", "Shop", EdgeKind::Choice, Some("Shop"), &[]),
+            edge("Start", "End", EdgeKind::Call, None, &[]),
+            edge("Start", "End", EdgeKind::Timeout, None, &[]),
+            edge("Start", "Sword", EdgeKind::Call, None, &["if $gold > 10"]),
+            // `else` is only taken if the preceding branches weren't.
+            edge("Start", "Shop", EdgeKind::Call, None, &["not ($gold > 10)"]),
+            // A call at the end of a passage is a jump.
+            edge("Start", "End", EdgeKind::Goto, None, &[]),
+            edge("Room:Enter", "Start", EdgeKind::Call, None, &[]),
+            // `Room` has an `onExit` to run, so its final call returns.
+            edge("Room:Enter", "Room:Cellar", EdgeKind::Call, None, &[]),
+        ]
+    );
+}
+
+#[test]
+fn test_story_graph_formats() {
+    let story = Story::load("tests/data/graph").unwrap();
+    let dot = build_story_graph_dot(&build_story_graph(&story));
+    for expected in [
+        "    subgraph \"cluster_global\" {\n        label = \"global\";\n        \"End\" [label = \"End\"];\n",
+        "        \"Room:Cellar\" [label = \"Cellar\", style = dashed, color = red];\n",
+        "    \"Start\" -> \"Sword\" [label = \"Buy a sword\\n[if $gold > 0]\"];\n",
+        "    \"Start\" -> \"Room:Enter\" [style = dashed];\n",
+        "    \"Start\" -> \"End\" [style = bold];\n",
+    ] {
+        assert!(dot.contains(expected), "Missing {:?} in:\n{}", expected, dot);
+    }
+    assert_eq!(build_story_graph_file(&story, "graph.dot").unwrap(), dot);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&build_story_graph_file(&story, "graph.json").unwrap()).unwrap();
+    assert_eq!(json["nodes"][4]["missing"], true);
+    assert_eq!(json["edges"][5]["kind"], "choice");
+    assert_eq!(json["edges"][5]["conditions"][0], "if $gold > 0");
+}
@@ -0,0 +1,115 @@
+use kataru::*;
+use kataru_ffi::{
+    build_line_ids, get_choice, get_choice_id, get_choices, init_runner, next, split_line_id,
+    validate_line_ids, Speech, SpeechOptions, RUNNER,
+};
+
+#[test]
+fn test_line_ids() {
+    let story = Story::load("tests/data/ids").unwrap();
+    let ids = build_line_ids(&story);
+    let summary: Vec<(&str, usize, Option<&str>, &str)> = ids
+        .iter()
+        .map(|id| {
+            (
+                id.passage.as_str(),
+                id.line,
+                id.choice.as_deref(),
+                id.text.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("End", 0, None, "Again."),
+            ("Start", 0, None, "Hello there."),
+            ("Start", 1, None, "Again."),
+            ("Start", 2, None, "Again."),
+            ("Start", 3, Some("Leave"), "Leave"),
+            ("Start", 3, Some("Stay #id:choice_stay"), "Stay"),
+        ]
+    );
+
+    // Explicit ids are used as is, identical lines get an occurrence suffix,
+    // and the passage is part of the hash.
+    assert_eq!(ids[1].id, "greeting");
+    assert!(ids[1].explicit);
+    assert_eq!(ids[3].id, format!("{}-2", ids[2].id));
+    assert_ne!(ids[0].id, ids[2].id);
+    assert_eq!(ids[5].id, "choice_stay");
+    assert!(ids[5].explicit);
+    validate_line_ids(&story).unwrap();
+}
+
+#[test]
+fn test_duplicate_line_ids() {
+    let story = Story::load("tests/data/duplicate_ids").unwrap();
+    let err = validate_line_ids(&story).unwrap_err();
+    assert!(format!("{:?}", err)
+        .contains("Line id 'greeting' in passage 'Start' is already used in passage 'End'."));
+}
+
+#[test]
+fn test_line_id_stripped_from_speech() {
+    let story = Story::load("tests/data/ids").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+    let Line::Dialogue(dialogue) = runner.next("").unwrap() else {
+        panic!("Expected dialogue.");
+    };
+    let speech = Speech::build(Some(&runner), &[], &dialogue, &SpeechOptions::default()).unwrap();
+    assert_eq!(speech.text, "Hello there.");
+}
+
+#[test]
+fn test_split_line_id() {
+    assert_eq!(
+        split_line_id("Hello there. #id:greeting"),
+        ("Hello there.", Some("greeting"))
+    );
+    assert_eq!(split_line_id("#id:greeting"), ("", Some("greeting")));
+
+    // Hashtags and numbers that aren't marked as ids are part of the text.
+    assert_eq!(split_line_id("You're #1"), ("You're #1", None));
+    assert_eq!(
+        split_line_id("Follow us #blessed"),
+        ("Follow us #blessed", None)
+    );
+    assert_eq!(
+        split_line_id("Hello#id:greeting"),
+        ("Hello#id:greeting", None)
+    );
+    assert_eq!(
+        split_line_id("Hi #id:two words"),
+        ("Hi #id:two words", None)
+    );
+}
+
+#[test]
+fn test_line_id_stripped_from_choices() {
+    let story_path = "tests/data/ids";
+    let bookmark_path = "tests/data/bookmark.yml";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+    let input = "";
+    for _ in 0..4 {
+        next(input.as_ptr() as *const i8, input.len());
+    }
+    assert_eq!(get_choices(), 2);
+    assert_eq!(get_choice(1).as_str(), "Stay");
+    assert_eq!(get_choice_id(1).as_str(), "choice_stay");
+
+    // The displayed label is mapped back onto the annotated source key.
+    let input = "Stay";
+    let result = next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(result.as_str(), "");
+    unsafe {
+        assert_eq!(RUNNER.as_ref().unwrap().passage(), "End");
+    }
+}
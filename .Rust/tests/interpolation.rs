@@ -0,0 +1,58 @@
+use kataru::*;
+use kataru_ffi::{validate_interpolation, Speech, SpeechOptions};
+
+#[test]
+fn test_interpolation_speech() {
+    let story = Story::load("tests/data/interpolation").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+    let Line::Dialogue(dialogue) = runner.next("").unwrap() else {
+        panic!("Expected dialogue.");
+    };
+    let speech = Speech::build(Some(&runner), &[], &dialogue, &SpeechOptions::default()).unwrap();
+    assert_eq!(
+        speech.text,
+        "You have a coin and 12,345.68 gold, but no ${missing}."
+    );
+
+    runner
+        .set_state(
+            StateMod {
+                var: "coins",
+                op: AssignOperator::None,
+            },
+            Value::Number(1500.0),
+        )
+        .unwrap();
+    let speech = Speech::build(Some(&runner), &[], &dialogue, &SpeechOptions::default()).unwrap();
+    assert!(speech.text.starts_with("You have 1500 coins"));
+
+    // Markup in values is shown as written rather than parsed.
+    let Line::Dialogue(dialogue) = runner.next("").unwrap() else {
+        panic!("Expected dialogue.");
+    };
+    let options = SpeechOptions {
+        rich_text: true,
+        ..SpeechOptions::default()
+    };
+    let speech = Speech::build(Some(&runner), &[], &dialogue, &options).unwrap();
+    assert_eq!(speech.text, "{speed=9}<i>Sir</i> is here.");
+    assert_eq!(
+        serde_json::to_string(&speech.spans).unwrap(),
+        r#"[{"start":0,"end":19,"tag":"bold"}]"#
+    );
+    assert_eq!(
+        serde_json::to_string(&speech.timeline).unwrap(),
+        r#"[{"index":19,"event":"pause","value":1.0}]"#
+    );
+}
+
+#[test]
+fn test_validate_interpolation() {
+    let story = Story::load("tests/data/interpolation").unwrap();
+    let err = validate_interpolation(&story).unwrap_err();
+    assert!(format!("{:?}", err).contains("Passage 'Start': Unknown variable 'missing'"));
+
+    let story = Story::load("tests/data/dialogue").unwrap();
+    validate_interpolation(&story).unwrap();
+}
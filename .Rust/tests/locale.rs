@@ -0,0 +1,159 @@
+use kataru::*;
+use kataru_ffi::{
+    build_line_ids, build_string_table_csv, build_string_table_xliff, get_choice, get_choices,
+    get_missing_translations, get_speech, init_runner, load_locale_table, next, set_locale,
+    StringTable, RUNNER,
+};
+
+#[test]
+fn test_string_tables() {
+    let story = Story::load("tests/data/ids").unwrap();
+    let ids = build_line_ids(&story);
+
+    let csv = build_string_table_csv(&story);
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("id,namespace,passage,speaker,source,translation")
+    );
+    assert_eq!(
+        lines.nth(1),
+        Some("greeting,global,Start,Alice,Hello there.,")
+    );
+
+    // Tables filled in by translators load back by id.
+    let translated = csv.replace(
+        "greeting,global,Start,Alice,Hello there.,",
+        "greeting,global,Start,Alice,Hello there.,\"Bonjour, \"\"toi\"\".\"",
+    );
+    let table = StringTable::from_csv(&translated).unwrap();
+    assert_eq!(table.get("greeting"), Some("Bonjour, \"toi\"."));
+    assert_eq!(table.translations.len(), 1);
+
+    let xliff = build_string_table_xliff(&story, "en");
+    assert!(xliff.contains(r#"<file original="kataru" source-language="en" datatype="plaintext">"#));
+    assert!(xliff.contains(&format!(r#"<trans-unit id="{}">"#, ids[0].id)));
+    let translated = xliff.replace(
+        "<source>Hello there.</source>",
+        "<source>Hello there.</source>\n        <target>Bonjour &amp; salut.</target>",
+    );
+    let table = StringTable::from_xliff(&translated).unwrap();
+    assert_eq!(table.get("greeting"), Some("Bonjour & salut."));
+    assert_eq!(table.translations.len(), 1);
+}
+
+#[test]
+fn test_xliff_from_tools() {
+    // Character references, CDATA, single quoted attributes and other attributes ending in `id`.
+    let xliff = concat!(
+        "<?xml version='1.0' encoding='UTF-8'?>\n",
+        "<xliff version='1.2'><file original='kataru' source-language='en'><body>\n",
+        "<trans-unit resid='other' id='greeting'><source>Hi.</source>",
+        "<target>L&#x2019;ami&#10;salut.</target></trans-unit>\n",
+        "<trans-unit id=\"farewell\"><target><![CDATA[Au <revoir> & adieu.]]></target></trans-unit>\n",
+        "<trans-unit id='empty'><target/></trans-unit>\n",
+        "</body></file></xliff>\n"
+    );
+    let table = StringTable::from_xliff(xliff).unwrap();
+    assert_eq!(table.get("greeting"), Some("L\u{2019}ami\nsalut."));
+    assert_eq!(table.get("farewell"), Some("Au <revoir> & adieu."));
+    assert_eq!(table.translations.len(), 2);
+
+    // Constructs that can't be represented are errors rather than dropped text.
+    let inline =
+        "<xliff><trans-unit id='a'><target>Hi <g id='1'>there</g></target></trans-unit></xliff>";
+    let err = StringTable::from_xliff(inline).unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported <g> element"),
+        "{}",
+        err
+    );
+    let missing_id = "<xliff><trans-unit><target>Hi</target></trans-unit></xliff>";
+    assert!(StringTable::from_xliff(missing_id).is_err());
+    let malformed = "<xliff><trans-unit id='a'><target>Hi</source></trans-unit></xliff>";
+    assert!(StringTable::from_xliff(malformed).is_err());
+}
+
+#[test]
+fn test_locale_overlay() {
+    let story_path = "tests/data/ids";
+    let bookmark_path = "tests/data/bookmark.yml";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+
+    // Translate the greeting and one of the two choices.
+    let story = Story::load(story_path).unwrap();
+    let leave = build_line_ids(&story)
+        .into_iter()
+        .find(|line_id| line_id.choice.as_deref() == Some("Leave"))
+        .unwrap();
+    let table_path = std::env::temp_dir().join("kataru_ffi_locale_fr.csv");
+    std::fs::write(
+        &table_path,
+        format!("id,translation\ngreeting,Bonjour.\n{},Partir\n", leave.id),
+    )
+    .unwrap();
+    let table_path = table_path.to_str().unwrap();
+    let locale = "fr";
+    let result = load_locale_table(
+        locale.as_ptr() as *const i8,
+        locale.len(),
+        table_path.as_ptr() as *const i8,
+        table_path.len(),
+    );
+    assert_eq!(result.as_str(), "");
+    let result = set_locale(locale.as_ptr() as *const i8, locale.len());
+    assert_eq!(result.as_str(), "");
+
+    let input = "";
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(get_speech().as_str(), "Bonjour.");
+
+    // The current line's speech is rebuilt when the locale changes.
+    let source_locale = "en";
+    set_locale(source_locale.as_ptr() as *const i8, source_locale.len());
+    assert_eq!(get_speech().as_str(), "Hello there.");
+    set_locale(locale.as_ptr() as *const i8, locale.len());
+    assert_eq!(get_speech().as_str(), "Bonjour.");
+
+    // Translations that fail to parse are reported rather than shown as written.
+    let broken_locale = "de";
+    let broken_path = std::env::temp_dir().join("kataru_ffi_locale_de.csv");
+    std::fs::write(&broken_path, "id,translation\ngreeting,Hallo.</b>\n").unwrap();
+    let broken_path = broken_path.to_str().unwrap();
+    load_locale_table(
+        broken_locale.as_ptr() as *const i8,
+        broken_locale.len(),
+        broken_path.as_ptr() as *const i8,
+        broken_path.len(),
+    );
+    let result = set_locale(broken_locale.as_ptr() as *const i8, broken_locale.len());
+    assert!(result.as_str().contains("</b>"), "{}", result.as_str());
+    set_locale(locale.as_ptr() as *const i8, locale.len());
+
+    // Untranslated lines and choices fall back to the source text.
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(get_speech().as_str(), "Again.");
+    next(input.as_ptr() as *const i8, input.len());
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(get_choices(), 2);
+    assert_eq!(get_choice(0).as_str(), "Partir");
+    assert_eq!(get_choice(1).as_str(), "Stay");
+
+    // Translated choices still branch on their source keys.
+    let input = "Partir";
+    let result = next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(result.as_str(), "");
+    unsafe {
+        assert_eq!(RUNNER.as_ref().unwrap().passage(), "End");
+    }
+
+    let missing = get_missing_translations(locale.as_ptr() as *const i8, locale.len());
+    let missing: serde_json::Value = serde_json::from_str(missing.as_str()).unwrap();
+    assert_eq!(missing.as_array().unwrap().len(), 4);
+}
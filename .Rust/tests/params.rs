@@ -0,0 +1,56 @@
+use kataru::*;
+use kataru_ffi::{
+    get_param_bool, get_param_count, get_param_name, get_param_number, get_param_string,
+    get_param_type, FFIStr, ParamType, LINE,
+};
+
+#[test]
+fn test_typed_params() {
+    let mut params = Params::new();
+    params.insert("speed".to_string(), Value::Number(2.5));
+    params.insert("loop".to_string(), Value::Bool(true));
+    params.insert("clip".to_string(), Value::String("Walk".to_string()));
+    unsafe {
+        LINE = Line::Command(Command {
+            name: "Play".to_string(),
+            params,
+        });
+    }
+
+    assert_eq!(get_param_count(), 3);
+    let params: Vec<(String, ParamType)> = (0..get_param_count())
+        .map(|i| (get_param_name(i).as_str().to_string(), get_param_type(i)))
+        .collect();
+    assert_eq!(
+        params,
+        vec![
+            ("speed".to_string(), ParamType::Number),
+            ("loop".to_string(), ParamType::Bool),
+            ("clip".to_string(), ParamType::String)
+        ]
+    );
+    assert_eq!(get_param_name(3), FFIStr::from(""));
+    assert_eq!(get_param_type(3), ParamType::None);
+
+    let get = |name: &str| (name.as_ptr() as *const i8, name.len());
+    let (speed, speed_len) = get("speed");
+    let (looping, looping_len) = get("loop");
+    let (clip, clip_len) = get("clip");
+    assert_eq!(get_param_number(speed, speed_len), 2.5);
+    assert!(get_param_bool(looping, looping_len));
+    assert_eq!(get_param_string(clip, clip_len), FFIStr::from("Walk"));
+
+    // Mismatched types and missing params fall back to defaults.
+    assert_eq!(get_param_number(clip, clip_len), 0.0);
+    assert!(!get_param_bool(speed, speed_len));
+    assert_eq!(get_param_string(looping, looping_len), FFIStr::from(""));
+    let (missing, missing_len) = get("missing");
+    assert_eq!(get_param_number(missing, missing_len), 0.0);
+
+    // Non-command lines have no params.
+    unsafe {
+        LINE = Line::End;
+    }
+    assert_eq!(get_param_count(), 0);
+    assert_eq!(get_param_type(0), ParamType::None);
+}
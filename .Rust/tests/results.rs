@@ -0,0 +1,117 @@
+use kataru::*;
+use kataru_ffi::{
+    complete, complete_command, get_param_count, get_params, init_runner, next,
+    validate_command_results, RUNNER,
+};
+
+#[test]
+fn test_complete_command() {
+    let story = Story::load("tests/data/results").unwrap();
+    validate_command_results(&story).unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+
+    let Line::Command(command) = runner.next("").unwrap() else {
+        panic!("Expected command.");
+    };
+    assert_eq!(command.name, "RollDice");
+    complete(&mut runner, &command, Value::Number(5.)).unwrap();
+    assert_eq!(runner.get_state("roll").unwrap(), &Value::Number(5.));
+    match runner.next("").unwrap() {
+        Line::Dialogue(dialogue) => assert_eq!(dialogue.text, "You win."),
+        line => panic!("Expected dialogue, got {:?}.", line),
+    }
+
+    // Results must match the type of the declared variable.
+    let Line::Command(command) = runner.run("Start".to_string()).unwrap() else {
+        panic!("Expected command.");
+    };
+    assert!(complete(&mut runner, &command, Value::String("six".to_string())).is_err());
+
+    let plain = Command {
+        name: "Wait".to_string(),
+        params: Params::new(),
+    };
+    assert!(complete(&mut runner, &plain, Value::Bool(true)).is_err());
+}
+
+#[test]
+fn test_undeclared_result() {
+    let mut story = Story::load("tests/data/results").unwrap();
+    let section = story.sections.get_mut(GLOBAL).unwrap();
+    section.config.state.clear();
+    assert_eq!(
+        format!("{:?}", validate_command_results(&story).unwrap_err()),
+        format!(
+            "{:?}",
+            error!("Command 'RollDice': result variable 'roll' is not declared.")
+        )
+    );
+}
+
+#[test]
+fn test_result_passed_in_call() {
+    let mut story = Story::load("tests/data/results").unwrap();
+    let mut validate_start = |line: &str| {
+        story.sections.get_mut(GLOBAL).unwrap().passages =
+            Passages::from_yml(&format!("Start:\n  - {}\n", line)).unwrap();
+        validate_command_results(&story).map_err(|err| err.to_string())
+    };
+
+    // Positional arguments fill parameters in order, so a second one would overwrite `$result`.
+    assert_eq!(
+        validate_start("RollDice: [6, 5]").unwrap_err(),
+        "Passage 'Start': command 'RollDice' is called with 2 arguments, which overwrites its '$result' parameter."
+    );
+    validate_start("RollDice: [20]").unwrap();
+    assert_eq!(
+        validate_start("RollDice: {$result: other}").unwrap_err(),
+        "Passage 'Start': command 'RollDice' passes a value for '$result'."
+    );
+}
+
+#[test]
+fn test_complete_command_declaring_namespace() {
+    let story = Story::load("tests/data/results").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+
+    // The result variable is resolved where the command is declared, not where it's called.
+    let Line::Command(command) = runner.run("Room:Roll".to_string()).unwrap() else {
+        panic!("Expected command.");
+    };
+    complete(&mut runner, &command, Value::Number(2.)).unwrap();
+    assert_eq!(runner.get_state("roll").unwrap(), &Value::Number(0.));
+    assert_eq!(runner.get_state("global:roll").unwrap(), &Value::Number(2.));
+}
+
+#[test]
+fn test_complete_command_ffi() {
+    let story_path = "tests/data/results";
+    let bookmark_path = "tests/data/bookmark.yml";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+    let input = "";
+    next(input.as_ptr() as *const i8, input.len());
+
+    // Handlers only see the command's own parameters.
+    assert_eq!(get_param_count(), 1);
+    assert_eq!(get_params().as_str(), r#"{"sides":6.0}"#);
+
+    let result = "5";
+    assert_eq!(
+        complete_command(result.as_ptr() as *const i8, result.len()).as_str(),
+        ""
+    );
+    unsafe {
+        assert_eq!(
+            RUNNER.as_ref().unwrap().get_state("roll").unwrap(),
+            &Value::Number(5.)
+        );
+    }
+}
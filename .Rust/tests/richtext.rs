@@ -0,0 +1,101 @@
+use kataru::*;
+use kataru_ffi::{RichText, Speech, SpeechOptions};
+
+#[test]
+fn test_rich_text() {
+    let no_passthrough: &[&str] = &[];
+
+    // Unclosed tags span to the end of the text.
+    let rich_text = RichText::parse("<size=200%><b>BLUE!!!!", no_passthrough);
+    assert_eq!(rich_text.text, "BLUE!!!!");
+    assert_eq!(
+        serde_json::to_string(&rich_text.spans).unwrap(),
+        r#"[{"start":0,"end":8,"tag":"size","value":"200%"},{"start":0,"end":8,"tag":"bold"}]"#
+    );
+
+    // Params, hex colors, self closing and custom tags.
+    let rich_text = RichText::parse(
+        "Hi <i>there</i>, <#FF0000>red</color> <wave speed=2 amp='3'>ok</wave><br/>!",
+        no_passthrough,
+    );
+    assert_eq!(rich_text.text, "Hi there, red ok!");
+    assert_eq!(
+        serde_json::to_string(&rich_text.spans).unwrap(),
+        concat!(
+            r#"[{"start":3,"end":8,"tag":"italic"},"#,
+            r##"{"start":10,"end":13,"tag":"color","value":"#FF0000"},"##,
+            r#"{"start":14,"end":16,"tag":"wave","params":{"amp":"3","speed":"2"}},"#,
+            r#"{"start":16,"end":16,"tag":"br"}]"#
+        )
+    );
+
+    // Text that only looks like tags is left alone, as are passthrough tags.
+    let rich_text = RichText::parse("1 < 2 <b>and</b> 3 > <u>2</u>", &["bold"]);
+    assert_eq!(rich_text.text, "1 < 2 <b>and</b> 3 > 2");
+    assert_eq!(
+        serde_json::to_string(&rich_text.spans).unwrap(),
+        r#"[{"start":21,"end":22,"tag":"underline"}]"#
+    );
+}
+
+#[test]
+fn test_rich_text_speech() {
+    let dialogue = Dialogue {
+        name: "Slime".to_string(),
+        text: "<b>Hey</b> you.".to_string(),
+        attributes: vec![AttributedSpan {
+            start: 11,
+            end: 14,
+            params: Map::new(),
+        }],
+    };
+
+    // Rich text is off by default.
+    let speech = Speech::build(None, &[], &dialogue, &SpeechOptions::default()).unwrap();
+    assert_eq!(speech.text, dialogue.text);
+    assert!(speech.spans.is_empty());
+
+    let options = SpeechOptions {
+        rich_text: true,
+        ..SpeechOptions::default()
+    };
+    let speech = Speech::build(None, &[], &dialogue, &options).unwrap();
+    assert_eq!(speech.text, "Hey you.");
+    assert_eq!(speech.spans.len(), 1);
+    assert_eq!(
+        (speech.attributes[0].start, speech.attributes[0].end),
+        (4, 7)
+    );
+}
+
+#[test]
+fn test_rich_text_speech_non_ascii() {
+    // Kataru reports attributes as byte offsets, "é" and "…" being two and three bytes.
+    let dialogue = Dialogue {
+        name: "Slime".to_string(),
+        text: "<b>Café</b>… you.".to_string(),
+        attributes: vec![AttributedSpan {
+            start: "<b>Café</b>… ".len(),
+            end: "<b>Café</b>… you".len(),
+            params: Map::new(),
+        }],
+    };
+    let options = SpeechOptions {
+        rich_text: true,
+        ..SpeechOptions::default()
+    };
+    let speech = Speech::build(None, &[], &dialogue, &options).unwrap();
+    assert_eq!(speech.text, "Café… you.");
+    assert_eq!((speech.spans[0].start, speech.spans[0].end), (0, 4));
+    assert_eq!(
+        (speech.attributes[0].start, speech.attributes[0].end),
+        (6, 9)
+    );
+
+    // Offsets are characters whether or not rich text is enabled.
+    let speech = Speech::build(None, &[], &dialogue, &SpeechOptions::default()).unwrap();
+    assert_eq!(
+        (speech.attributes[0].start, speech.attributes[0].end),
+        (13, 16)
+    );
+}
@@ -0,0 +1,45 @@
+use kataru::*;
+use kataru_ffi::{Markers, Speech, SpeechOptions};
+
+#[test]
+fn test_markers() {
+    let markers = Markers::mark("a{pause=0.5}b {{speed=2}} {$var + 1} {speed=fast}c");
+    let timeline = markers.resolve(&markers.text);
+    assert_eq!(timeline.text, "ab {{speed=2}} {$var + 1} c");
+    assert_eq!(
+        serde_json::to_string(&timeline.events).unwrap(),
+        r#"[{"index":1,"event":"pause","value":0.5},{"index":26,"event":"speed","value":"fast"}]"#
+    );
+}
+
+#[test]
+fn test_timeline_speech() {
+    let story = Story::load("tests/data/dialogue").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+    let Line::Dialogue(dialogue) = runner.next("").unwrap() else {
+        panic!("Expected dialogue.");
+    };
+    let options = SpeechOptions {
+        rich_text: true,
+        ..SpeechOptions::default()
+    };
+    let speech = Speech::build(Some(&runner), &[], &dialogue, &options).unwrap();
+
+    // Markers are extracted from the source text, before Kataru evaluates `{...}` expressions.
+    assert_eq!(speech.text, "Wait... hurry, now! You have 3 coins {sic}.");
+    assert_eq!(
+        serde_json::to_string(&speech.timeline).unwrap(),
+        r#"[{"index":4,"event":"pause","value":0.5},{"index":8,"event":"speed","value":2.0}]"#
+    );
+
+    // Spans and attributes are mapped onto the clean text.
+    assert_eq!(
+        serde_json::to_string(&speech.spans).unwrap(),
+        r#"[{"start":8,"end":13,"tag":"bold"}]"#
+    );
+    assert_eq!(
+        (speech.attributes[0].start, speech.attributes[0].end),
+        (15, 18)
+    );
+}
@@ -0,0 +1,93 @@
+use kataru::*;
+use kataru_ffi::{take_timeout_default, TimeoutDefault};
+
+fn speech(line: Line) -> String {
+    match line {
+        Line::Dialogue(dialogue) => dialogue.text,
+        line => panic!("Expected dialogue, got {:?}.", line),
+    }
+}
+
+fn raw_choices<'s>(runner: &'s Runner, passage: &str) -> &'s RawChoices {
+    let passage = &runner.story().sections[GLOBAL].passages[passage];
+    passage
+        .iter()
+        .find_map(|line| match line {
+            RawLine::Choices(choices) => Some(choices),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_timeout_default() {
+    let story = Story::load("tests/data/timeout").unwrap();
+    let bookmark = Bookmark::load("tests/data/bookmark.yml").unwrap();
+    let mut runner = Runner::init(bookmark, story, true).unwrap();
+
+    // Passage defaults are called, returning to the line after the choices.
+    assert_eq!(
+        TimeoutDefault::from(raw_choices(&runner, "Start")),
+        TimeoutDefault::Passage {
+            passage: "Left".to_string()
+        }
+    );
+    runner.next("").unwrap();
+    let Line::Choices(choices) = runner.next("").unwrap() else {
+        panic!("Expected choices.");
+    };
+    assert_eq!(choices.timeout, 5.0);
+    assert_eq!(speech(take_timeout_default(&mut runner).unwrap()), "Left.");
+    assert_eq!(runner.bookmark().stack.len(), 1);
+    assert_eq!(speech(runner.next("").unwrap()), "Back.");
+
+    // Defaults at the end of a passage are jumps, like choices there.
+    let Line::Choices(_) = runner.run("TailCall".to_string()).unwrap() else {
+        panic!("Expected choices.");
+    };
+    assert_eq!(speech(take_timeout_default(&mut runner).unwrap()), "Left.");
+    assert!(runner.bookmark().stack.is_empty());
+    assert_eq!(runner.next("").unwrap(), Line::End);
+
+    // Embedded defaults continue with their lines, then after the choices.
+    // Their length includes nested lines.
+    assert_eq!(
+        TimeoutDefault::from(raw_choices(&runner, "Embedded")),
+        TimeoutDefault::Lines { lines: 3 }
+    );
+    let Line::Choices(_) = runner.run("Embedded".to_string()).unwrap() else {
+        panic!("Expected choices.");
+    };
+    assert_eq!(
+        speech(take_timeout_default(&mut runner).unwrap()),
+        "Too slow."
+    );
+    assert_eq!(speech(runner.next("").unwrap()), "Far too slow.");
+    assert_eq!(speech(runner.next("").unwrap()), "After.");
+
+    // Without a default, the timeout can't be taken.
+    assert_eq!(
+        TimeoutDefault::from(raw_choices(&runner, "NoDefault")),
+        TimeoutDefault::None
+    );
+    let Line::Choices(_) = runner.run("NoDefault".to_string()).unwrap() else {
+        panic!("Expected choices.");
+    };
+    let err = take_timeout_default(&mut runner).unwrap_err();
+    assert!(format!("{:?}", err).contains("Choices have no default to take on timeout."));
+}
+
+#[test]
+fn test_timeout_default_json() {
+    assert_eq!(
+        serde_json::to_string(&TimeoutDefault::Passage {
+            passage: "Left".to_string()
+        })
+        .unwrap(),
+        r#"{"type":"passage","passage":"Left"}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&TimeoutDefault::None).unwrap(),
+        r#"{"type":"none"}"#
+    );
+}
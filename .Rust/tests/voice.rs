@@ -0,0 +1,112 @@
+use kataru::*;
+use kataru_ffi::{
+    build_line_ids, build_voice_manifest, build_voice_manifest_csv, get_speech, get_voice_clip_key,
+    init_runner, next,
+};
+
+#[test]
+fn test_voice_manifest() {
+    let story = Story::load("tests/data/ids").unwrap();
+    let manifest = build_voice_manifest(&story);
+
+    // Choices aren't spoken, so only the four dialogue lines are included.
+    assert_eq!(manifest.len(), 4);
+    assert_eq!(manifest[1].key, "global_Start_Alice_greeting");
+    assert_eq!(manifest[1].text, "Hello there.");
+
+    let ids = build_line_ids(&story);
+    assert_eq!(manifest[0].id, ids[0].id);
+    assert_eq!(manifest[0].key, format!("global_End_Alice_{}", ids[0].id));
+
+    let csv = build_voice_manifest_csv(&story);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("key,id,speaker,namespace,passage,text"));
+    assert_eq!(
+        lines.nth(1),
+        Some("global_Start_Alice_greeting,greeting,Alice,global,Start,Hello there.")
+    );
+}
+
+#[test]
+fn test_voice_manifest_narration() {
+    let story = Story::load("tests/data/voice").unwrap();
+    let manifest = build_voice_manifest(&story);
+
+    // Lines without a speaker are spoken by the last speaker in the passage,
+    // but only within the branch they're in.
+    let spoken: Vec<(&str, &str)> = manifest
+        .iter()
+        .map(|line| (line.speaker.as_str(), line.text.as_str()))
+        .collect();
+    assert_eq!(
+        spoken,
+        vec![
+            ("Room:Alice", "Welcome to my room."),
+            ("Alice", "Hello there."),
+            ("Alice", "And this is Alice too."),
+            ("Bob", "Hi again."),
+            (
+                "Alice",
+                "Still Alice, since Bob only speaks in the other branch."
+            ),
+        ]
+    );
+
+    // Local characters are qualified, so they don't share keys with global ones.
+    assert!(manifest[0].key.starts_with("Room_Enter_Room-Alice_"));
+    assert!(manifest[2].key.starts_with("global_Start_Alice_"));
+}
+
+#[test]
+fn test_voice_clip_key() {
+    let story_path = "tests/data/ids";
+    let bookmark_path = "tests/data/bookmark.yml";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+    let input = "";
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(get_voice_clip_key().as_str(), "global_Start_Alice_greeting");
+
+    // The runner plays narration as the last speaker, so it's voiced by them too.
+    let story_path = "tests/data/voice";
+    init_runner(
+        story_path.as_ptr() as *const i8,
+        story_path.len(),
+        bookmark_path.as_ptr() as *const i8,
+        bookmark_path.len(),
+        true,
+    );
+    next(input.as_ptr() as *const i8, input.len());
+    let speech_key = get_voice_clip_key().as_str().to_string();
+    next(input.as_ptr() as *const i8, input.len());
+    let narration_key = get_voice_clip_key();
+    let narration_key = narration_key.as_str();
+    assert!(narration_key.starts_with("global_Start_Alice_"));
+    assert_ne!(narration_key, speech_key);
+    let manifest = build_voice_manifest(&Story::load(story_path).unwrap());
+    assert_eq!(narration_key, manifest[2].key);
+
+    // Narration after branches with different last speakers isn't in the manifest,
+    // and neither is narration opening a passage, so they have no key either.
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(get_voice_clip_key().as_str(), manifest[4].key);
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(
+        get_speech().as_str(),
+        "Not voiced, since the speaker depends on the branch taken."
+    );
+    assert_eq!(get_voice_clip_key().as_str(), "");
+    next(input.as_ptr() as *const i8, input.len());
+    let input = "Leave";
+    next(input.as_ptr() as *const i8, input.len());
+    assert_eq!(
+        get_speech().as_str(),
+        "Not voiced, since the speaker depends on how the passage was reached."
+    );
+    assert_eq!(get_voice_clip_key().as_str(), "");
+}
@@ -103,6 +103,11 @@ namespace Kataru
             ).ThrowIfError();
         }
 
+        [DllImport("kataru_ffi")]
+        static extern void set_strict_interpolation(bool strict);
+        public static void SetStrictInterpolation(bool strict) =>
+            set_strict_interpolation(strict);
+
         [DllImport("kataru_ffi")]
         static extern FFIStr save_story(byte[] path, UIntPtr length);
         public static void SaveStory(string path)
@@ -124,6 +129,14 @@ namespace Kataru
             next(bytes, (UIntPtr)bytes.Length).ThrowIfError();
         }
 
+        [DllImport("kataru_ffi")]
+        static extern FFIStr choose(UIntPtr index);
+        public static void Choose(int index) => choose((UIntPtr)index).ThrowIfError();
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr choose_timeout();
+        public static void ChooseTimeout() => choose_timeout().ThrowIfError();
+
         [DllImport("kataru_ffi")]
         static extern LineTag tag();
         public static LineTag Tag() => tag();
@@ -153,26 +166,86 @@ namespace Kataru
         static extern FFIStr get_choice(UIntPtr i);
         static string GetChoice(int i) => get_choice((UIntPtr)i).ToString();
 
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_choice_id(UIntPtr i);
+        static string GetChoiceId(int i) => get_choice_id((UIntPtr)i).ToString();
+
         [DllImport("kataru_ffi")]
         static extern double get_timeout();
         static double GetTimeout() => get_timeout();
 
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_timeout_default();
+        static TimeoutDefault GetTimeoutDefault()
+        {
+            string json = get_timeout_default().ToString();
+            return JsonConvert.DeserializeObject<TimeoutDefault>(json);
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_choice_details();
+        static ChoiceDetails[] GetChoiceDetails()
+        {
+            string json = get_choice_details().ToString();
+            return JsonConvert.DeserializeObject<ChoiceDetails[]>(json);
+        }
+
         public static Choices LoadChoices()
         {
             var choices = new List<string>();
+            var ids = new List<string>();
             int numChoices = GetChoices();
-            for (int i = 0; i < numChoices; ++i) choices.Add(GetChoice(i));
-            return new Choices() { choices = choices, timeout = GetTimeout() };
+            for (int i = 0; i < numChoices; ++i)
+            {
+                choices.Add(GetChoice(i));
+                ids.Add(GetChoiceId(i));
+            }
+            return new Choices() { choices = choices, ids = ids, details = GetChoiceDetails(), timeout = GetTimeout(), timeoutDefault = GetTimeoutDefault() };
         }
         #endregion
 
+        enum ParamType { None, String, Number, Bool }
+
         [DllImport("kataru_ffi")]
-        static extern FFIStr get_params();
+        static extern UIntPtr get_param_count();
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_param_name(UIntPtr i);
+
+        [DllImport("kataru_ffi")]
+        static extern ParamType get_param_type(UIntPtr i);
+
+        [DllImport("kataru_ffi")]
+        static extern double get_param_number(byte[] name, UIntPtr length);
+
+        [DllImport("kataru_ffi")]
+        static extern bool get_param_bool(byte[] name, UIntPtr length);
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_param_string(byte[] name, UIntPtr length);
+
         static Dictionary<string, object> GetParams()
         {
-            string json = get_params().ToString();
-            Debug.Log($"Got json: {json}");
-            return JsonConvert.DeserializeObject<Dictionary<string, object>>(json);
+            var parameters = new Dictionary<string, object>();
+            int count = (int)get_param_count();
+            for (int i = 0; i < count; ++i)
+            {
+                string name = get_param_name((UIntPtr)i).ToString();
+                var bytes = Encoding.UTF8.GetBytes(name);
+                switch (get_param_type((UIntPtr)i))
+                {
+                    case ParamType.String:
+                        parameters[name] = get_param_string(bytes, (UIntPtr)bytes.Length).ToString();
+                        break;
+                    case ParamType.Number:
+                        parameters[name] = get_param_number(bytes, (UIntPtr)bytes.Length);
+                        break;
+                    case ParamType.Bool:
+                        parameters[name] = get_param_bool(bytes, (UIntPtr)bytes.Length);
+                        break;
+                }
+            }
+            return parameters;
         }
 
         [DllImport("kataru_ffi")]
@@ -188,12 +261,57 @@ namespace Kataru
         #region Commands
         [DllImport("kataru_ffi")]
         static extern FFIStr get_command();
-        public static Command GetCommand() => new Command() { name = get_command().ToString(), parameters = GetParams() };
+        public static Command GetCommand() => new Command()
+        {
+            name = get_command().ToString(),
+            character = GetCommandCharacter(),
+            baseName = GetCommandBaseName(),
+            parameters = GetParams()
+        };
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_command_character();
+        static string GetCommandCharacter() => get_command_character().ToString();
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_command_base_name();
+        static string GetCommandBaseName() => get_command_base_name().ToString();
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_command_qualified_name();
+        public static string GetCommandQualifiedName() => get_command_qualified_name().ToString();
 
         public static InputCommand LoadInputCommand()
         {
             return new InputCommand() { prompt = "Not implemented" };
         }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr list_commands(byte[] @namespace, UIntPtr length);
+        public static CommandInfo[] ListCommands(string @namespace)
+        {
+            var bytes = Encoding.UTF8.GetBytes(@namespace);
+            string json = list_commands(bytes, (UIntPtr)bytes.Length).ToString();
+            return JsonConvert.DeserializeObject<CommandInfo[]>(json);
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr complete_command(byte[] result, UIntPtr length);
+        public static void CompleteCommand(object result)
+        {
+            var bytes = Encoding.UTF8.GetBytes(JsonConvert.SerializeObject(result));
+            complete_command(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr check_handlers(byte[] command_handlers, UIntPtr command_handlers_length, byte[] character_handlers, UIntPtr character_handlers_length);
+        public static HandlerReport CheckHandlers(IEnumerable<string> commandHandlers, IEnumerable<string> characterHandlers)
+        {
+            var commandBytes = Encoding.UTF8.GetBytes(JsonConvert.SerializeObject(commandHandlers));
+            var characterBytes = Encoding.UTF8.GetBytes(JsonConvert.SerializeObject(characterHandlers));
+            string json = check_handlers(commandBytes, (UIntPtr)commandBytes.Length, characterBytes, (UIntPtr)characterBytes.Length).ToString();
+            return JsonConvert.DeserializeObject<HandlerReport>(json);
+        }
         #endregion
 
         #region Dialogue
@@ -214,14 +332,129 @@ namespace Kataru
             return JsonConvert.DeserializeObject<AttributedSpan[]>(json);
         }
 
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_rich_text_spans();
+        static RichSpan[] GetRichTextSpans()
+        {
+            string json = get_rich_text_spans().ToString();
+            return JsonConvert.DeserializeObject<RichSpan[]>(json);
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_timeline();
+        static TimelineEvent[] GetTimeline()
+        {
+            string json = get_timeline().ToString();
+            return JsonConvert.DeserializeObject<TimelineEvent[]>(json);
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr set_rich_text(bool enabled);
+        [DllImport("kataru_ffi")]
+        static extern FFIStr set_rich_text_passthrough(byte[] tags, UIntPtr length);
+        public static void SetRichText(bool enabled, string passthrough)
+        {
+            var bytes = Encoding.UTF8.GetBytes(passthrough ?? "");
+            set_rich_text(enabled).ThrowIfError();
+            set_rich_text_passthrough(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_line_id();
+        public static string GetLineId() => get_line_id().ToString();
+
         public static Dialogue LoadDialogue() => new Dialogue()
         {
+            id = GetLineId(),
             name = GetSpeaker(),
             text = GetSpeech(),
-            attributes = GetAttributes()
+            attributes = GetAttributes(),
+            spans = GetRichTextSpans(),
+            timeline = GetTimeline()
         };
         #endregion
 
+        #region Characters
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_character_config(byte[] name, UIntPtr length);
+        public static Character GetCharacterConfig(string name)
+        {
+            var bytes = Encoding.UTF8.GetBytes(name);
+            string json = get_character_config(bytes, (UIntPtr)bytes.Length).ToString();
+            return JsonConvert.DeserializeObject<Character>(json);
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr list_characters(byte[] @namespace, UIntPtr length);
+        public static Character[] ListCharacters(string @namespace)
+        {
+            var bytes = Encoding.UTF8.GetBytes(@namespace);
+            string json = list_characters(bytes, (UIntPtr)bytes.Length).ToString();
+            return JsonConvert.DeserializeObject<Character[]>(json);
+        }
+        #endregion
+
+        #region Locale
+        [DllImport("kataru_ffi")]
+        static extern FFIStr extract_string_table(byte[] path, UIntPtr path_length, byte[] source_locale, UIntPtr source_locale_length);
+        public static void ExtractStringTable(string path, string sourceLocale)
+        {
+            var path_bytes = Encoding.UTF8.GetBytes(path);
+            var locale_bytes = Encoding.UTF8.GetBytes(sourceLocale);
+            extract_string_table(path_bytes, (UIntPtr)path_bytes.Length, locale_bytes, (UIntPtr)locale_bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr load_locale_table(byte[] locale, UIntPtr locale_length, byte[] path, UIntPtr path_length);
+        public static void LoadLocaleTable(string locale, string path)
+        {
+            var locale_bytes = Encoding.UTF8.GetBytes(locale);
+            var path_bytes = Encoding.UTF8.GetBytes(path);
+            load_locale_table(locale_bytes, (UIntPtr)locale_bytes.Length, path_bytes, (UIntPtr)path_bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr set_locale(byte[] locale, UIntPtr length);
+        public static void SetLocale(string locale)
+        {
+            var bytes = Encoding.UTF8.GetBytes(locale);
+            set_locale(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_missing_translations(byte[] locale, UIntPtr length);
+        public static LineInfo[] GetMissingTranslations(string locale)
+        {
+            var bytes = Encoding.UTF8.GetBytes(locale);
+            string json = get_missing_translations(bytes, (UIntPtr)bytes.Length).ToString();
+            return JsonConvert.DeserializeObject<LineInfo[]>(json);
+        }
+        #endregion
+
+        #region Voice
+        [DllImport("kataru_ffi")]
+        static extern FFIStr voice_manifest(byte[] path, UIntPtr length);
+        public static void VoiceManifest(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path);
+            voice_manifest(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr get_voice_clip_key();
+        public static string GetVoiceClipKey() => get_voice_clip_key().ToString();
+        #endregion
+
+        #region Graph
+        [DllImport("kataru_ffi")]
+        static extern FFIStr story_graph(byte[] path, UIntPtr length);
+        public static void StoryGraph(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path);
+            story_graph(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+        #endregion
+
         #region Codegen
         [DllImport("kataru_ffi")]
         static extern FFIStr codegen_consts(byte[] path, UIntPtr length);
@@ -231,9 +464,65 @@ namespace Kataru
             codegen_consts(bytes, (UIntPtr)bytes.Length).ThrowIfError();
         }
 
+        [DllImport("kataru_ffi")]
+        static extern FFIStr codegen_c_header(byte[] path, UIntPtr length);
+        public static void CodegenCHeader(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path);
+            codegen_c_header(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr codegen_typescript(byte[] path, UIntPtr length);
+        public static void CodegenTypeScript(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path);
+            codegen_typescript(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr codegen_json(byte[] path, UIntPtr length);
+        public static void CodegenJson(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path);
+            codegen_json(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr codegen_handlers(byte[] path, UIntPtr length);
+        public static void CodegenHandlers(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path);
+            codegen_handlers(bytes, (UIntPtr)bytes.Length).ThrowIfError();
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern void set_codegen_nested(bool enabled);
+        public static void SetCodegenNested(bool enabled) => set_codegen_nested(enabled);
+
+        [DllImport("kataru_ffi")]
+        static extern void set_codegen_template(byte[] path, UIntPtr length);
+        public static void SetCodegenTemplate(string path)
+        {
+            var bytes = Encoding.UTF8.GetBytes(path ?? "");
+            set_codegen_template(bytes, (UIntPtr)bytes.Length);
+        }
+
+        [DllImport("kataru_ffi")]
+        static extern void set_codegen_split(bool enabled);
+        public static void SetCodegenSplit(bool enabled) => set_codegen_split(enabled);
+
         [DllImport("kataru_ffi")]
         static extern bool codegen_was_updated();
         public static bool CodegenWasUpdated() => codegen_was_updated();
+
+        [DllImport("kataru_ffi")]
+        static extern FFIStr codegen_changed_files();
+        public static string[] CodegenChangedFiles()
+        {
+            string json = codegen_changed_files().ToString();
+            return JsonConvert.DeserializeObject<string[]>(json);
+        }
         #endregion
     }
 }
\ No newline at end of file
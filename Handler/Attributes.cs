@@ -65,7 +65,9 @@ namespace Kataru
         public static Delegate CreateDelegate(this MethodInfo methodInfo, object target)
         {
             var types = methodInfo.GetParameters().Select(p => p.ParameterType);
-            return Action.CreateDelegate(Expression.GetActionType(types.ToArray()), target, methodInfo.Name);
+            // Bind the method itself rather than its name, so private methods of base classes are found.
+            // Virtual and abstract methods still dispatch to the target's override.
+            return Delegate.CreateDelegate(Expression.GetActionType(types.ToArray()), target, methodInfo);
         }
     }
 
@@ -94,43 +96,53 @@ namespace Kataru
         /// <returns></returns>
         protected IEnumerable<NamedDelegate> GetActionsForAttribute<A>() where A : NamedAttribute
         {
-            var type = this.GetType();
-            foreach (var methodInfo in type.GetMethods(BindingFlags.DeclaredOnly | BindingFlags.Instance | BindingFlags.NonPublic | BindingFlags.Public))
+            // Walk up to base classes too, so that attributed abstract methods of generated handler bases are found.
+            // Each method is registered once, using the attributes of its most derived declaration that has any.
+            var registered = new HashSet<MethodInfo>();
+            for (var type = this.GetType(); type != null && type != typeof(Attributed); type = type.BaseType)
             {
-                foreach (var attribute in methodInfo.GetCustomAttributes<A>(false))
+                foreach (var methodInfo in type.GetMethods(BindingFlags.DeclaredOnly | BindingFlags.Instance | BindingFlags.NonPublic | BindingFlags.Public))
                 {
-                    // [KataruCharacterHandler] -> prefixedOnly = true -> Name
-                    // [KataruCharacterHandler("Character")] -> prefixedOnly = true -> attribute.Name
-                    // [KataruCommandHandler] -> prefixed = false -> method.Name
-                    // [KataruCommandHandler("Name")] -> prefixed = false -> attribute.Name
-
-                    // If prefix is set to something other than empty string, the action name will be "{prefix}.{name}".
-                    // Otherwise it will just be "name".
-
-                    string name = "";
-                    if (attribute.PrefixOnly)
+                    var attributes = methodInfo.GetCustomAttributes<A>(false).ToList();
+                    if (attributes.Count == 0 || !registered.Add(methodInfo.GetBaseDefinition()))
                     {
-                        name = Name;
+                        continue;
                     }
-                    else
+                    foreach (var attribute in attributes)
                     {
-                        string suffix = attribute.Name.Length > 0 ? attribute.Name : methodInfo.Name;
-                        if (attribute.Prefixed)
+                        // [KataruCharacterHandler] -> prefixedOnly = true -> Name
+                        // [KataruCharacterHandler("Character")] -> prefixedOnly = true -> attribute.Name
+                        // [KataruCommandHandler] -> prefixed = false -> method.Name
+                        // [KataruCommandHandler("Name")] -> prefixed = false -> attribute.Name
+
+                        // If prefix is set to something other than empty string, the action name will be "{prefix}.{name}".
+                        // Otherwise it will just be "name".
+
+                        string name = "";
+                        if (attribute.PrefixOnly)
                         {
-                            name = $"{Name}.{suffix}";
+                            name = Name;
                         }
                         else
                         {
-                            name = suffix;
+                            string suffix = attribute.Name.Length > 0 ? attribute.Name : methodInfo.Name;
+                            if (attribute.Prefixed)
+                            {
+                                name = $"{Name}.{suffix}";
+                            }
+                            else
+                            {
+                                name = suffix;
+                            }
                         }
-                    }
 
-                    yield return new NamedDelegate
-                    {
-                        name = name,
-                        @delegate = methodInfo.CreateDelegate(this),
-                        autoNext = attribute.AutoNext
-                    };
+                        yield return new NamedDelegate
+                        {
+                            name = name,
+                            @delegate = methodInfo.CreateDelegate(this),
+                            autoNext = attribute.AutoNext
+                        };
+                    }
                 }
             }
         }
@@ -16,15 +16,55 @@ namespace Kataru
 
         // Tooltips
         public const string codegenPathTooltip = "The path where Kataru generated C# code should be saved, relative to project root. Standard installations use Assets/Scripts/Kataru/Constants.Generated.cs.",
+            codegenNestedTooltip = "If enabled, generated passages and characters are nested by namespace, e.g. Passages.Room1.Start instead of Passages.Room1_Start.",
+            codegenSplitTooltip = "If enabled, each namespace is generated into its own file next to the Kataru source path, e.g. Constants.Generated.Room1.cs, which is only rewritten when that namespace changes.",
+            codegenTemplatePathTooltip = "Optional path to a Jinja template to generate code from instead of the built in C# output, relative to project root. The default template is bundled with the Kataru source as Constants.cs.jinja. Templates decide their own layout, so nesting and splitting must be disabled when one is set.",
+            codegenHeaderPathTooltip = "Optional path where the constants should also be generated as a C header, relative to project root, e.g. for native plugins.",
+            codegenTypeScriptPathTooltip = "Optional path where the constants should also be generated as TypeScript, relative to project root, e.g. for web tooling.",
+            codegenJsonPathTooltip = "Optional path where a JSON manifest of the story's passages, characters, commands and variables should be saved, relative to project root.",
+            handlersPathTooltip = "Optional path where abstract handler base classes should be generated, relative to project root. Each declared command and character command gets an abstract method, so missing handlers become compile errors.",
             storyPathTooltip = "The path to the story folder containing Kataru YAML files (.yml), relative to Application.dataPath(Assets / when in editor). Should be in an Editor directory to avoid inclusion in build.",
             bookmarkPathTooltip = "The path to the default bookmark file (.yml or .bin), relative to Application.dataPath(Assets / when in editor).",
             targetPathTooltip = "Path to output compiled Kataru script (.bin), relative to Application.dataPath (Assets/ when in editor).",
-            savePathTooltip = "Path to store the player's save data, relative to Application.persistentDataPath.";
+            savePathTooltip = "Path to store the player's save data, relative to Application.persistentDataPath.",
+            voiceManifestPathTooltip = "Optional path where a voice-over manifest of every spoken line should be saved on compile, relative to project root. Written as JSON if the path ends in .json, otherwise CSV.",
+            storyGraphPathTooltip = "Optional path where a graph of passages and the choices, calls and jumps between them should be saved on compile, relative to project root. Written as JSON if the path ends in .json, otherwise GraphViz DOT.",
+            richTextTooltip = "If enabled, rich text tags are stripped from dialogue text and provided as spans.",
+            richTextPassthroughTooltip = "Comma separated list of tags to leave in dialogue text when rich text is enabled, e.g. tags the text engine renders natively.",
+            strictInterpolationTooltip = "If enabled, validation fails when dialogue references a ${variable} that isn't declared in the story state.";
 
         [SerializeField]
         [Tooltip(codegenPathTooltip)]
         public string codegenPath;
 
+        [SerializeField]
+        [Tooltip(codegenNestedTooltip)]
+        public bool codegenNested;
+
+        [SerializeField]
+        [Tooltip(codegenSplitTooltip)]
+        public bool codegenSplit;
+
+        [SerializeField]
+        [Tooltip(codegenTemplatePathTooltip)]
+        public string codegenTemplatePath;
+
+        [SerializeField]
+        [Tooltip(codegenHeaderPathTooltip)]
+        public string codegenHeaderPath;
+
+        [SerializeField]
+        [Tooltip(codegenTypeScriptPathTooltip)]
+        public string codegenTypeScriptPath;
+
+        [SerializeField]
+        [Tooltip(codegenJsonPathTooltip)]
+        public string codegenJsonPath;
+
+        [SerializeField]
+        [Tooltip(handlersPathTooltip)]
+        public string handlersPath;
+
         [SerializeField]
         [Tooltip(storyPathTooltip)]
         public string storyPath;
@@ -41,6 +81,26 @@ namespace Kataru
         [Tooltip(savePathTooltip)]
         public string savePath;
 
+        [SerializeField]
+        [Tooltip(voiceManifestPathTooltip)]
+        public string voiceManifestPath;
+
+        [SerializeField]
+        [Tooltip(storyGraphPathTooltip)]
+        public string storyGraphPath;
+
+        [SerializeField]
+        [Tooltip(richTextTooltip)]
+        public bool richText;
+
+        [SerializeField]
+        [Tooltip(richTextPassthroughTooltip)]
+        public string richTextPassthrough;
+
+        [SerializeField]
+        [Tooltip(strictInterpolationTooltip)]
+        public bool strictInterpolation;
+
         private static KataruSettings instance = null;
         public static KataruSettings Get(bool createIfMissing = false)
         {
@@ -85,10 +145,22 @@ namespace Kataru
         class Styles
         {
             public static GUIContent codegenPath = new GUIContent("Kataru Source Path"),
+                codegenNested = new GUIContent("Nest Constants By Namespace"),
+                codegenSplit = new GUIContent("Split Constants By Namespace"),
+                codegenTemplatePath = new GUIContent("Codegen Template Path"),
+                codegenHeaderPath = new GUIContent("C Header Path"),
+                codegenTypeScriptPath = new GUIContent("TypeScript Path"),
+                codegenJsonPath = new GUIContent("Symbol Manifest Path"),
+                handlersPath = new GUIContent("Handlers Source Path"),
                 storyPath = new GUIContent("Story Path"),
                 bookmarkPath = new GUIContent("Bookmark Path"),
                 targetPath = new GUIContent("Target Path"),
-                savePath = new GUIContent("Save Path");
+                savePath = new GUIContent("Save Path"),
+                voiceManifestPath = new GUIContent("Voice Manifest Path"),
+                storyGraphPath = new GUIContent("Story Graph Path"),
+                richText = new GUIContent("Rich Text"),
+                richTextPassthrough = new GUIContent("Rich Text Passthrough"),
+                strictInterpolation = new GUIContent("Strict Interpolation");
         }
 
 #if UNITY_EDITOR
@@ -108,6 +180,34 @@ namespace Kataru
             EditorGUILayout.HelpBox(KataruSettings.codegenPathTooltip, MessageType.None);
             EditorGUILayout.Space();
 
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("codegenNested"), Styles.codegenNested);
+            EditorGUILayout.HelpBox(KataruSettings.codegenNestedTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("codegenSplit"), Styles.codegenSplit);
+            EditorGUILayout.HelpBox(KataruSettings.codegenSplitTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("codegenTemplatePath"), Styles.codegenTemplatePath);
+            EditorGUILayout.HelpBox(KataruSettings.codegenTemplatePathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("codegenHeaderPath"), Styles.codegenHeaderPath);
+            EditorGUILayout.HelpBox(KataruSettings.codegenHeaderPathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("codegenTypeScriptPath"), Styles.codegenTypeScriptPath);
+            EditorGUILayout.HelpBox(KataruSettings.codegenTypeScriptPathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("codegenJsonPath"), Styles.codegenJsonPath);
+            EditorGUILayout.HelpBox(KataruSettings.codegenJsonPathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("handlersPath"), Styles.handlersPath);
+            EditorGUILayout.HelpBox(KataruSettings.handlersPathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
             EditorGUILayout.PropertyField(serializedSettings.FindProperty("storyPath"), Styles.storyPath);
             EditorGUILayout.HelpBox(KataruSettings.storyPathTooltip, MessageType.None);
             EditorGUILayout.Space();
@@ -124,6 +224,26 @@ namespace Kataru
             EditorGUILayout.HelpBox(KataruSettings.savePathTooltip, MessageType.None);
             EditorGUILayout.Space();
 
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("voiceManifestPath"), Styles.voiceManifestPath);
+            EditorGUILayout.HelpBox(KataruSettings.voiceManifestPathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("storyGraphPath"), Styles.storyGraphPath);
+            EditorGUILayout.HelpBox(KataruSettings.storyGraphPathTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("richText"), Styles.richText);
+            EditorGUILayout.HelpBox(KataruSettings.richTextTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("richTextPassthrough"), Styles.richTextPassthrough);
+            EditorGUILayout.HelpBox(KataruSettings.richTextPassthroughTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
+            EditorGUILayout.PropertyField(serializedSettings.FindProperty("strictInterpolation"), Styles.strictInterpolation);
+            EditorGUILayout.HelpBox(KataruSettings.strictInterpolationTooltip, MessageType.None);
+            EditorGUILayout.Space();
+
             if (EditorGUI.EndChangeCheck())
             {
                 serializedSettings.ApplyModifiedProperties();
@@ -72,7 +72,9 @@ namespace Kataru
             isWaiting = false;
 
             // Only load the story on Init.
+            FFI.SetStrictInterpolation(settings.strictInterpolation);
             FFI.InitRunner(targetPath, bookmarkPath, validate: true);
+            FFI.SetRichText(settings.richText, settings.richTextPassthrough);
 
             isInitialized = true;
         }
@@ -175,6 +177,23 @@ namespace Kataru
         public static T GetState<T>(string key) => FFI.GetState<T>(key);
         public static string GetNamespace() => FFI.GetNamespace();
         public static string GetPassage() => FFI.GetPassage();
+        public static Character GetCharacterConfig(string name) => FFI.GetCharacterConfig(name);
+        public static Character[] ListCharacters(string @namespace) => FFI.ListCharacters(@namespace);
+        public static CommandInfo[] ListCommands(string @namespace) => FFI.ListCommands(@namespace);
+        /// <summary>
+        /// Assigns the result of the current command to the variable its declaration names with `$result`.
+        /// The result must have the same type as the variable. Call this from a command handler before continuing the story.
+        /// </summary>
+        public static void CompleteCommand(object result) => FFI.CompleteCommand(result);
+        /// <summary>
+        /// Compares the declared commands and characters against the currently registered handlers.
+        /// </summary>
+        public static HandlerReport CheckHandlers() => FFI.CheckHandlers(CommandDelegates.Keys, CharacterDelegates.Keys);
+        public static void ExtractStringTable(string path, string sourceLocale) => FFI.ExtractStringTable(path, sourceLocale);
+        public static void LoadLocaleTable(string locale, string path) => FFI.LoadLocaleTable(locale, path);
+        public static void SetLocale(string locale) => FFI.SetLocale(locale);
+        public static LineInfo[] GetMissingTranslations(string locale) => FFI.GetMissingTranslations(locale);
+        public static string GetVoiceClipKey() => FFI.GetVoiceClipKey();
 
         public static void RunPassageAtLine(string passage, int line)
         {
@@ -246,6 +265,52 @@ namespace Kataru
             return ReadLine();
         }
 
+        /// <summary>
+        /// Progress the story by picking the choice at the given index of the current choices.
+        /// Unlike passing a label to Next, this works with localized and duplicate labels.
+        /// Throws if the index is out of range.
+        /// </summary>
+        /// <param name="index"></param>
+        public static LineTag Choose(int index)
+        {
+#if UNITY_EDITOR
+            Debug.Log($"[Runner] Kataru.Runner.Choose({index}).");
+#endif
+            if (isWaiting)
+            {
+#if UNITY_EDITOR
+                Debug.LogWarning($@"Called Runner.Choose while runner was busy waiting.
+                                    Don't call Runner.Choose until Runner.DelayedNext has finished.");
+#endif
+                return LineTag.End;
+            }
+
+            FFI.Choose(index);
+            return ReadLine();
+        }
+
+        /// <summary>
+        /// Progress the story past the current choices as if their timeout ran out.
+        /// Throws if the choices have no default.
+        /// </summary>
+        public static LineTag ChooseTimeout()
+        {
+#if UNITY_EDITOR
+            Debug.Log($"[Runner] Kataru.Runner.ChooseTimeout().");
+#endif
+            if (isWaiting)
+            {
+#if UNITY_EDITOR
+                Debug.LogWarning($@"Called Runner.ChooseTimeout while runner was busy waiting.
+                                    Don't call Runner.ChooseTimeout until Runner.DelayedNext has finished.");
+#endif
+                return LineTag.End;
+            }
+
+            FFI.ChooseTimeout();
+            return ReadLine();
+        }
+
         private static LineTag ReadLine()
         {
             Tag = FFI.Tag();
@@ -406,16 +471,46 @@ namespace Kataru
             try
             {
                 bool validate = true;
+                var settings = KataruSettings.Get(createIfMissing: true);
+                FFI.SetStrictInterpolation(settings.strictInterpolation);
                 FFI.InitRunner(storyPath, bookmarkPath, validate);
 
                 Debug.Log($"[Runner] Story at '{storyPath}' validated. Saving compiled story to '{targetPath}'.");
                 FFI.SaveStory(targetPath);
+                FFI.SetCodegenNested(settings.codegenNested);
+                FFI.SetCodegenSplit(settings.codegenSplit);
+                FFI.SetCodegenTemplate(settings.codegenTemplatePath);
                 FFI.CodegenConsts(codegenPath);
+                if (!string.IsNullOrEmpty(settings.codegenHeaderPath))
+                {
+                    FFI.CodegenCHeader(settings.codegenHeaderPath);
+                }
+                if (!string.IsNullOrEmpty(settings.codegenTypeScriptPath))
+                {
+                    FFI.CodegenTypeScript(settings.codegenTypeScriptPath);
+                }
+                if (!string.IsNullOrEmpty(settings.codegenJsonPath))
+                {
+                    FFI.CodegenJson(settings.codegenJsonPath);
+                }
+                if (!string.IsNullOrEmpty(settings.handlersPath))
+                {
+                    FFI.CodegenHandlers(settings.handlersPath);
+                }
+                if (!string.IsNullOrEmpty(settings.voiceManifestPath))
+                {
+                    FFI.VoiceManifest(settings.voiceManifestPath);
+                }
+                if (!string.IsNullOrEmpty(settings.storyGraphPath))
+                {
+                    FFI.StoryGraph(settings.storyGraphPath);
+                }
 
                 // Force unity to recompile using the newly generated source code.
-                if (FFI.CodegenWasUpdated())
+                string[] changedFiles = FFI.CodegenChangedFiles();
+                if (changedFiles.Length > 0)
                 {
-                    Debug.Log($"[Runner] Constants file generated at {targetPath}");
+                    Debug.Log($"[Runner] Generated files changed: {string.Join(", ", changedFiles)}");
                     UnityEditor.Compilation.CompilationPipeline.RequestScriptCompilation();
                 }
             }
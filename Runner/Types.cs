@@ -1,3 +1,4 @@
+using System;
 using System.Collections.Generic;
 using System.Reflection;
 using Newtonsoft.Json;
@@ -23,12 +24,12 @@ namespace Kataru
     public struct AttributedSpan
     {
         /// <summary>
-        /// Where in the stripped text the span begins.
+        /// Character index in the stripped text where the span begins.
         /// </summary>
         public int start;
 
         /// <summary>
-        /// Where in the stripped text the span ends.
+        /// Character index in the stripped text where the span ends.
         /// </summary>
         public int end;
 
@@ -41,11 +42,73 @@ namespace Kataru
         public override string ToString() => JsonConvert.SerializeObject(this);
     }
 
+    /// <summary>
+    /// A span of dialogue text covered by a rich text tag.
+    /// </summary>
+    public struct RichSpan
+    {
+        /// <summary>
+        /// Character index in the stripped text where the span begins.
+        /// </summary>
+        public int start;
+
+        /// <summary>
+        /// Character index in the stripped text where the span ends.
+        /// </summary>
+        public int end;
+
+        /// <summary>
+        /// Normalized tag name, e.g. "bold", "italic", "size", "color" or a custom tag.
+        /// </summary>
+        public string tag;
+
+        /// <summary>
+        /// Value assigned to the tag itself, e.g. "200%" for &lt;size=200%&gt;.
+        /// </summary>
+        public string value;
+
+        /// <summary>
+        /// Any additional parameters on the tag.
+        /// </summary>
+        [JsonProperty("params")]
+        public Dictionary<string, string> parameters;
+
+        public override string ToString() => JsonConvert.SerializeObject(this);
+    }
+
+    /// <summary>
+    /// A typewriter event extracted from an inline marker such as {pause=0.5} or {speed=2}.
+    /// </summary>
+    public struct TimelineEvent
+    {
+        /// <summary>
+        /// Character index in the dialogue text the event occurs before.
+        /// </summary>
+        public int index;
+
+        /// <summary>
+        /// Name of the event, e.g. "pause" or "speed".
+        /// </summary>
+        [JsonProperty("event")]
+        public string name;
+
+        /// <summary>
+        /// Value of the event.
+        /// </summary>
+        public object value;
+
+        public override string ToString() => JsonConvert.SerializeObject(this);
+    }
+
     /// <summary>
     /// Represents a single line of dialogue.
     /// </summary>
     public class Dialogue
     {
+        /// <summary>
+        /// Stable id of this line, either annotated with `#id:name` or hashed from its content.
+        /// </summary>
+        public string id;
         /// <summary>
         /// Name of the dialogue speaker.
         /// </summary>
@@ -58,6 +121,72 @@ namespace Kataru
         /// List of attributed span annotations on this dialogue line.
         /// </summary>
         public AttributedSpan[] attributes;
+        /// <summary>
+        /// Rich text spans stripped from the text. Empty unless rich text is enabled.
+        /// </summary>
+        public RichSpan[] spans;
+        /// <summary>
+        /// Typewriter events extracted from inline markers, ordered by index.
+        /// </summary>
+        public TimelineEvent[] timeline;
+    }
+
+    /// <summary>
+    /// A line of text or choice label in the story, identified by its stable id.
+    /// </summary>
+    public class LineInfo
+    {
+        /// <summary>
+        /// Stable id of the line.
+        /// </summary>
+        public string id;
+        /// <summary>
+        /// Namespace containing the line.
+        /// </summary>
+        public string @namespace;
+        /// <summary>
+        /// Passage containing the line.
+        /// </summary>
+        public string passage;
+        /// <summary>
+        /// Index of the line in the passage.
+        /// </summary>
+        public int line;
+        /// <summary>
+        /// The choice label, if this is a choice.
+        /// </summary>
+        public string choice;
+        /// <summary>
+        /// Name of the speaker, if this is dialogue.
+        /// </summary>
+        public string speaker;
+        /// <summary>
+        /// Source text of the line.
+        /// </summary>
+        public string text;
+        /// <summary>
+        /// True if the id was annotated with `#id:name` rather than hashed.
+        /// </summary>
+        public bool @explicit;
+    }
+
+    /// <summary>
+    /// A character's declared config, resolved from the current namespace.
+    /// </summary>
+    public class Character
+    {
+        /// <summary>
+        /// Qualified name of the character.
+        /// </summary>
+        public string name;
+        /// <summary>
+        /// Namespace the character was declared in.
+        /// </summary>
+        public string @namespace;
+        /// <summary>
+        /// Config fields declared for this character, or null if none.
+        /// </summary>
+        public Dictionary<string, object> config;
     }
 
     /// <summary>
@@ -70,6 +199,16 @@ namespace Kataru
         /// </summary>
         public string name;
 
+        /// <summary>
+        /// Namespace qualified character for character commands, or empty for plain commands.
+        /// </summary>
+        public string character;
+
+        /// <summary>
+        /// Name of the command without its namespace or character.
+        /// </summary>
+        public string baseName;
+
         /// <summary>
         /// Parameters
         /// </summary>
@@ -83,7 +222,7 @@ namespace Kataru
         /// <returns></returns>
         public T Get<T>(string key)
         {
-            return (T)parameters[key];
+            return (T)Convert.ChangeType(parameters[key], typeof(T));
         }
 
         /// <summary>
@@ -94,17 +233,117 @@ namespace Kataru
         public object[] Params(MethodInfo methodInfo)
         {
             ParameterInfo[] paramInfos = methodInfo.GetParameters();
+            if (paramInfos.Length == 1 && IsParamsStruct(paramInfos[0].ParameterType))
+            {
+                return new object[] { ParamsStruct(paramInfos[0].ParameterType) };
+            }
+
             object[] parameters = new object[paramInfos.Length];
             for (int i = 0; i < paramInfos.Length; ++i)
             {
-                if (!this.parameters.TryGetValue(paramInfos[i].Name, out parameters[i]))
+                if (!this.parameters.TryGetValue(paramInfos[i].Name, out object value))
                 {
                     throw new KeyNotFoundException($"Parameter '{paramInfos[i].Name}' was not provided.");
                 }
+                // Numbers are always doubles, so convert them to the handler's parameter type.
+                parameters[i] = Convert.ChangeType(value, paramInfos[i].ParameterType);
             }
 
             return parameters;
         }
+
+        /// <summary>
+        /// True if the handler takes a generated params struct, e.g. `GlobalCommandParams`.
+        /// </summary>
+        static bool IsParamsStruct(Type type) => type.IsValueType && !type.IsPrimitive && !type.IsEnum;
+
+        /// <summary>
+        /// Fill a generated params struct, starting from its declared defaults.
+        /// </summary>
+        /// <param name="type"></param>
+        /// <returns></returns>
+        object ParamsStruct(Type type)
+        {
+            FieldInfo defaultField = type.GetField("Default", BindingFlags.Public | BindingFlags.Static);
+            object @struct = defaultField != null ? defaultField.GetValue(null) : Activator.CreateInstance(type);
+            foreach (FieldInfo field in type.GetFields(BindingFlags.Public | BindingFlags.Instance))
+            {
+                if (parameters.TryGetValue(field.Name, out object value))
+                {
+                    field.SetValue(@struct, Convert.ChangeType(value, field.FieldType));
+                }
+            }
+            return @struct;
+        }
+    }
+
+    /// <summary>
+    /// A command declared in the story config, resolved from a namespace.
+    /// </summary>
+    public class CommandInfo
+    {
+        /// <summary>
+        /// Qualified name of the command, e.g. `Room:Open` or `$character.SetAnimatorTrigger`.
+        /// </summary>
+        public string name;
+        /// <summary>
+        /// Namespace the command was declared in.
+        /// </summary>
+        public string @namespace;
+        /// <summary>
+        /// Name of the command without its namespace or `$character.` prefix.
+        /// </summary>
+        public string base_name;
+        /// <summary>
+        /// True if this is a character command, called as `Character.Command`.
+        /// </summary>
+        public bool character;
+        /// <summary>
+        /// Declared parameters in order.
+        /// </summary>
+        public CommandParamInfo[] @params;
+        /// <summary>
+        /// State variable the command's result is assigned to, or null if it doesn't return one.
+        /// </summary>
+        public string result;
+    }
+
+    /// <summary>
+    /// A declared command parameter with its default value.
+    /// </summary>
+    public class CommandParamInfo
+    {
+        /// <summary>
+        /// Name of the parameter.
+        /// </summary>
+        public string name;
+        /// <summary>
+        /// Default value of the parameter.
+        /// </summary>
+        public object @default;
+        /// <summary>
+        /// One of "string", "number" or "bool", inferred from the default value.
+        /// </summary>
+        public string type;
+    }
+
+    /// <summary>
+    /// Mismatches between the story's declared commands and characters and the registered handlers.
+    /// </summary>
+    public class HandlerReport
+    {
+        /// <summary>
+        /// Declared commands with no registered handler.
+        /// </summary>
+        public List<string> unhandled_commands;
+        /// <summary>
+        /// Registered command handlers that don't match a declared command, e.g. because of a typo.
+        /// </summary>
+        public List<string> undeclared_handlers;
+        /// <summary>
+        /// Declared characters with no registered character handler.
+        /// </summary>
+        public List<string> unhandled_characters;
     }
 
     /// <summary>
@@ -118,9 +357,75 @@ namespace Kataru
         /// </summary>
         public List<string> choices;
         /// <summary>
+        /// Stable ids of each choice, in the same order as choices.
+        /// </summary>
+        public List<string> ids;
+        /// <summary>
+        /// Every choice on this line in source order, including unavailable ones.
+        /// </summary>
+        public ChoiceDetails[] details;
+        /// <summary>
         /// How long the user has to make the choice in seconds.
         /// </summary>
         public double timeout;
+        /// <summary>
+        /// What happens when the timeout runs out.
+        /// </summary>
+        public TimeoutDefault timeoutDefault;
+    }
+
+    /// <summary>
+    /// The default a choice falls back to when its timeout runs out, declared with `default:` in the story.
+    /// </summary>
+    public class TimeoutDefault
+    {
+        /// <summary>
+        /// One of "passage", "lines" or "none".
+        /// </summary>
+        public string type;
+        /// <summary>
+        /// The passage called on timeout, if type is "passage".
+        /// </summary>
+        public string passage;
+        /// <summary>
+        /// The number of embedded default lines including nested ones, if type is "lines".
+        /// </summary>
+        public int lines;
+    }
+
+    /// <summary>
+    /// A choice along with where it leads and whether it can currently be chosen.
+    /// </summary>
+    public class ChoiceDetails
+    {
+        /// <summary>
+        /// Source key of the choice, which is passed to Next when choosing.
+        /// </summary>
+        public string label;
+        /// <summary>
+        /// Display text in the current locale, stripped of attribute tags.
+        /// </summary>
+        public string text;
+        /// <summary>
+        /// Stable id of the choice.
+        /// </summary>
+        public string id;
+        /// <summary>
+        /// Target passage, or null if the choice continues with embedded lines.
+        /// </summary>
+        public string target;
+        /// <summary>
+        /// Whether the choice can currently be chosen.
+        /// </summary>
+        public bool available;
+        /// <summary>
+        /// The condition that failed if the choice is unavailable.
+        /// </summary>
+        public string reason;
+        /// <summary>
+        /// Attribute tags in the label, e.g. &lt;locked&gt;.
+        /// </summary>
+        public RichSpan[] attributes;
     }
 
     /// <summary>